@@ -0,0 +1,392 @@
+//! Agent manifest registry.
+//!
+//! CLI agents used to be a hardcoded enum (`shell_profile::CLIAgent`) with
+//! match-arm logic in `commands::parse_agent_type`, plus a handful of
+//! string-aliasing hacks (`amp` -> Claude Code, `opencode`/`factory-droid`
+//! -> Codex) because those tools don't have their own enum variant. That
+//! doesn't scale: every new agent needs a recompile, and the aliases are a
+//! lie (those tools aren't actually Claude Code or Codex, they just happen
+//! to expose a similar base-url env var).
+//!
+//! Instead, an [`AgentManifest`] is plain data describing one agent: its
+//! env vars, whether it needs an API key, and how its base URL should be
+//! shaped. A handful of manifests ship built in; users (or installers) can
+//! drop additional manifest files into the agents directory and they show
+//! up alongside the built-ins with no code changes.
+
+use crate::shell_profile::ShellType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AgentRegistryError {
+    #[error("failed to read manifest: {0}")]
+    ReadError(String),
+    #[error("failed to write manifest: {0}")]
+    WriteError(String),
+    #[error("invalid manifest: {0}")]
+    InvalidManifest(String),
+    #[error("unknown agent: {0}")]
+    UnknownAgent(String),
+}
+
+/// How an agent's base-url env var expects its value shaped. Most
+/// coding-assistant CLIs speak an OpenAI-compatible API and want the `/v1`
+/// suffix; a few point straight at the proxy root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum EndpointMode {
+    #[default]
+    OpenAiCompatible,
+    Raw,
+}
+
+impl EndpointMode {
+    fn base_url(&self, port: u16) -> String {
+        match self {
+            EndpointMode::OpenAiCompatible => format!("http://127.0.0.1:{}/v1", port),
+            EndpointMode::Raw => format!("http://127.0.0.1:{}", port),
+        }
+    }
+}
+
+/// Describes one CLI agent Zest knows how to point at the local proxy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentManifest {
+    pub id: String,
+    pub name: String,
+    pub env_var: String,
+    #[serde(default)]
+    pub api_key_env_var: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub requires_api_key: bool,
+    #[serde(default)]
+    pub mode: EndpointMode,
+    /// Default model identifiers the agent should use for each routing
+    /// slot (e.g. `"default"`, `"fast"`), for agents whose config format
+    /// needs an explicit model name alongside the base URL.
+    #[serde(default)]
+    pub model_slots: HashMap<String, String>,
+    /// Other ids this manifest should also resolve under (e.g. `opencode`
+    /// resolving to the `codex` manifest's shape).
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Executable names to search for when launching this agent directly
+    /// (see `commands::launch_agent`). Falls back to `id` when empty.
+    #[serde(default)]
+    pub binary_names: Vec<String>,
+    /// True for manifests shipped with Zest; false for ones the user
+    /// installed. Not part of the on-disk JSON — it's derived from where
+    /// the manifest was loaded from.
+    #[serde(skip, default)]
+    pub builtin: bool,
+}
+
+impl AgentManifest {
+    fn matches(&self, query: &str) -> bool {
+        self.id.eq_ignore_ascii_case(query) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(query))
+    }
+
+    /// The base URL this agent's env var should be set to for the proxy
+    /// running on `port`, shaped according to `mode`.
+    pub fn base_url(&self, port: u16) -> String {
+        self.mode.base_url(port)
+    }
+
+    /// Render this agent's block for insertion into a shell profile.
+    pub fn render_profile_config(&self, shell: ShellType, port: u16, api_key: Option<&str>) -> String {
+        let base_url = self.mode.base_url(port);
+        let key = self.api_key_env_var.as_deref().zip(api_key);
+
+        match shell {
+            ShellType::Zsh | ShellType::Bash => {
+                let mut config = format!("export {}=\"{}\"\n", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    config.push_str(&format!("export {}=\"{}\"\n", key_var, key));
+                }
+                config
+            }
+            ShellType::Fish => {
+                let mut config = format!("set -gx {} \"{}\"\n", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    config.push_str(&format!("set -gx {} \"{}\"\n", key_var, key));
+                }
+                config
+            }
+            ShellType::Powershell => {
+                let mut config = format!("$env:{} = \"{}\"\n", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    config.push_str(&format!("$env:{} = \"{}\"\n", key_var, key));
+                }
+                config
+            }
+            ShellType::Cmd => {
+                let mut config = format!("set {}={}\n", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    config.push_str(&format!("set {}={}\n", key_var, key));
+                }
+                config
+            }
+        }
+    }
+
+    /// Render a single copy-pasteable line/command for the given shell.
+    pub fn render_env_command(&self, shell: ShellType, port: u16, api_key: Option<&str>) -> String {
+        let base_url = self.mode.base_url(port);
+        let key = self.api_key_env_var.as_deref().zip(api_key);
+
+        match shell {
+            ShellType::Zsh | ShellType::Bash => {
+                let mut cmd = format!("export {}=\"{}\"", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    cmd.push_str(&format!(" && export {}=\"{}\"", key_var, key));
+                }
+                cmd
+            }
+            ShellType::Fish => {
+                let mut cmd = format!("set -gx {} \"{}\"", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    cmd.push_str(&format!("; set -gx {} \"{}\"", key_var, key));
+                }
+                cmd
+            }
+            ShellType::Powershell => {
+                let mut cmd = format!("$env:{} = \"{}\"", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    cmd.push_str(&format!("; $env:{} = \"{}\"", key_var, key));
+                }
+                cmd
+            }
+            ShellType::Cmd => {
+                let mut cmd = format!("set {}={}", self.env_var, base_url);
+                if let Some((key_var, key)) = key {
+                    cmd.push_str(&format!(" & set {}={}", key_var, key));
+                }
+                cmd
+            }
+        }
+    }
+}
+
+/// The agents Zest ships support for out of the box.
+fn builtin_manifests() -> Vec<AgentManifest> {
+    let mut manifests = vec![
+        AgentManifest {
+            id: "claude-code".to_string(),
+            name: "Claude Code".to_string(),
+            env_var: "ANTHROPIC_BASE_URL".to_string(),
+            api_key_env_var: Some("ANTHROPIC_API_KEY".to_string()),
+            description: "Anthropic's Claude AI coding assistant".to_string(),
+            requires_api_key: true,
+            mode: EndpointMode::OpenAiCompatible,
+            model_slots: HashMap::new(),
+            aliases: vec!["claude".to_string(), "anthropic".to_string(), "amp".to_string()],
+            binary_names: vec!["claude".to_string()],
+            builtin: true,
+        },
+        AgentManifest {
+            id: "gemini-cli".to_string(),
+            name: "Gemini CLI".to_string(),
+            env_var: "GEMINI_API_BASE".to_string(),
+            api_key_env_var: None,
+            description: "Google's Gemini AI assistant".to_string(),
+            requires_api_key: false,
+            mode: EndpointMode::OpenAiCompatible,
+            model_slots: HashMap::new(),
+            aliases: vec!["gemini".to_string()],
+            binary_names: vec!["gemini".to_string()],
+            builtin: true,
+        },
+        AgentManifest {
+            id: "codex".to_string(),
+            name: "Codex (OpenAI)".to_string(),
+            env_var: "OPENAI_BASE_URL".to_string(),
+            api_key_env_var: Some("OPENAI_API_KEY".to_string()),
+            description: "OpenAI's Codex coding assistant".to_string(),
+            requires_api_key: true,
+            mode: EndpointMode::OpenAiCompatible,
+            model_slots: HashMap::new(),
+            aliases: vec!["openai".to_string(), "opencode".to_string(), "factory-droid".to_string()],
+            binary_names: vec!["codex".to_string()],
+            builtin: true,
+        },
+        AgentManifest {
+            id: "qwen".to_string(),
+            name: "Qwen".to_string(),
+            env_var: "QWEN_BASE_URL".to_string(),
+            api_key_env_var: Some("QWEN_API_KEY".to_string()),
+            description: "Alibaba's Qwen AI assistant".to_string(),
+            requires_api_key: true,
+            mode: EndpointMode::OpenAiCompatible,
+            model_slots: HashMap::new(),
+            aliases: vec![],
+            binary_names: vec!["qwen".to_string()],
+            builtin: true,
+        },
+        AgentManifest {
+            id: "iflow".to_string(),
+            name: "iFlow".to_string(),
+            env_var: "IFLOW_BASE_URL".to_string(),
+            api_key_env_var: Some("IFLOW_API_KEY".to_string()),
+            description: "iFlow AI assistant".to_string(),
+            requires_api_key: true,
+            mode: EndpointMode::OpenAiCompatible,
+            model_slots: HashMap::new(),
+            aliases: vec![],
+            binary_names: vec!["iflow".to_string()],
+            builtin: true,
+        },
+        AgentManifest {
+            id: "antigravity".to_string(),
+            name: "Antigravity".to_string(),
+            env_var: "ANTIGRAVITY_BASE_URL".to_string(),
+            api_key_env_var: Some("ANTIGRAVITY_API_KEY".to_string()),
+            description: "Antigravity AI assistant".to_string(),
+            requires_api_key: true,
+            mode: EndpointMode::OpenAiCompatible,
+            model_slots: HashMap::new(),
+            aliases: vec![],
+            binary_names: vec!["antigravity".to_string()],
+            builtin: true,
+        },
+    ];
+    for manifest in &mut manifests {
+        manifest.builtin = true;
+    }
+    manifests
+}
+
+/// Directory holding user-installed agent manifests (`<id>.json`).
+fn manifests_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Zest")
+        .join("agents")
+}
+
+fn load_user_manifests() -> Vec<AgentManifest> {
+    let Ok(entries) = std::fs::read_dir(manifests_dir()) else {
+        return Vec::new();
+    };
+
+    let mut manifests = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else { continue };
+        match serde_json::from_str::<AgentManifest>(&content) {
+            Ok(mut manifest) => {
+                manifest.builtin = false;
+                manifests.push(manifest);
+            }
+            Err(e) => log::warn!("skipping invalid agent manifest {}: {}", path.display(), e),
+        }
+    }
+    manifests
+}
+
+/// Built-in manifests plus user-installed ones. A user manifest whose `id`
+/// matches a built-in replaces it, so users can override the defaults.
+pub fn all_manifests() -> Vec<AgentManifest> {
+    let mut by_id: HashMap<String, AgentManifest> = HashMap::new();
+    for manifest in builtin_manifests() {
+        by_id.insert(manifest.id.clone(), manifest);
+    }
+    for manifest in load_user_manifests() {
+        by_id.insert(manifest.id.clone(), manifest);
+    }
+
+    let mut manifests: Vec<AgentManifest> = by_id.into_values().collect();
+    manifests.sort_by(|a, b| a.id.cmp(&b.id));
+    manifests
+}
+
+/// Resolve a user-supplied agent id/alias (e.g. from the frontend) against
+/// the registry, case-insensitively.
+pub fn find(query: &str) -> Result<AgentManifest, AgentRegistryError> {
+    all_manifests()
+        .into_iter()
+        .find(|manifest| manifest.matches(query))
+        .ok_or_else(|| AgentRegistryError::UnknownAgent(query.to_string()))
+}
+
+/// Validate and install a manifest file into the user manifests directory.
+pub fn install_manifest(source_path: &str) -> Result<AgentManifest, AgentRegistryError> {
+    let content = std::fs::read_to_string(source_path)
+        .map_err(|e| AgentRegistryError::ReadError(e.to_string()))?;
+    let manifest: AgentManifest = serde_json::from_str(&content)
+        .map_err(|e| AgentRegistryError::InvalidManifest(e.to_string()))?;
+
+    if manifest.id.trim().is_empty() || manifest.env_var.trim().is_empty() {
+        return Err(AgentRegistryError::InvalidManifest(
+            "manifest must have a non-empty \"id\" and \"env_var\"".to_string(),
+        ));
+    }
+
+    let dir = manifests_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| AgentRegistryError::WriteError(e.to_string()))?;
+    std::fs::write(dir.join(format!("{}.json", manifest.id)), &content)
+        .map_err(|e| AgentRegistryError::WriteError(e.to_string()))?;
+
+    let mut installed = manifest;
+    installed.builtin = false;
+    Ok(installed)
+}
+
+/// Remove a user-installed manifest. Built-in manifests can't be removed
+/// this way (there's no file backing them to delete).
+pub fn remove_manifest(id: &str) -> Result<(), AgentRegistryError> {
+    let path = manifests_dir().join(format!("{}.json", id));
+    if !path.exists() {
+        return Err(AgentRegistryError::UnknownAgent(id.to_string()));
+    }
+    std::fs::remove_file(&path).map_err(|e| AgentRegistryError::WriteError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_manifests_resolve_by_alias() {
+        let amp = find("amp").expect("amp should alias claude-code");
+        assert_eq!(amp.id, "claude-code");
+
+        let opencode = find("opencode").expect("opencode should alias codex");
+        assert_eq!(opencode.id, "codex");
+    }
+
+    #[test]
+    fn test_render_profile_config_bash() {
+        let manifest = find("claude-code").unwrap();
+        let config = manifest.render_profile_config(ShellType::Bash, 8317, Some("test-key"));
+        assert!(config.contains("export ANTHROPIC_BASE_URL=\"http://127.0.0.1:8317/v1\""));
+        assert!(config.contains("export ANTHROPIC_API_KEY=\"test-key\""));
+    }
+
+    #[test]
+    fn test_render_profile_config_fish() {
+        let manifest = find("claude-code").unwrap();
+        let config = manifest.render_profile_config(ShellType::Fish, 8317, None);
+        assert!(config.contains("set -gx ANTHROPIC_BASE_URL"));
+        assert!(!config.contains("ANTHROPIC_API_KEY"));
+    }
+
+    #[test]
+    fn test_render_profile_config_powershell() {
+        let manifest = find("claude-code").unwrap();
+        let config = manifest.render_profile_config(ShellType::Powershell, 8317, None);
+        assert!(config.contains("$env:ANTHROPIC_BASE_URL"));
+    }
+
+    #[test]
+    fn test_unknown_agent_errors() {
+        assert!(find("not-a-real-agent").is_err());
+    }
+}
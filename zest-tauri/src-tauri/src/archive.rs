@@ -0,0 +1,92 @@
+//! Pure-Rust archive extraction shared by the proxy-binary installer
+//! (`proxy::extract_and_install`) and the app's own self-updater
+//! (`updater::apply_update`).
+//!
+//! The archive type is detected from the data's magic bytes rather than
+//! the asset's file extension (a release can rename `.tar.gz` to anything),
+//! and only the first entry matching a caller-supplied predicate is read
+//! into memory instead of unpacking the whole archive to a temp directory.
+
+use std::io::Read;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("no entry in archive matched the expected binary name")]
+    EntryNotFound,
+    #[error("failed to read archive: {0}")]
+    Read(String),
+}
+
+enum ArchiveFormat {
+    Zip,
+    TarGz,
+    TarXz,
+    Raw,
+}
+
+/// Sniff the archive format from its leading magic bytes. Anything
+/// unrecognized is treated as a bare, already-executable binary rather than
+/// an error, matching how a raw (non-archived) release asset is handled.
+fn detect_format(data: &[u8]) -> ArchiveFormat {
+    if data.starts_with(&[0x50, 0x4b, 0x03, 0x04]) || data.starts_with(&[0x50, 0x4b, 0x05, 0x06]) {
+        ArchiveFormat::Zip
+    } else if data.starts_with(&[0x1f, 0x8b]) {
+        ArchiveFormat::TarGz
+    } else if data.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        ArchiveFormat::TarXz
+    } else {
+        ArchiveFormat::Raw
+    }
+}
+
+/// Extract the first entry whose path satisfies `matches_name`, returning
+/// its bytes and whether the archive recorded it as executable. If `data`
+/// isn't a recognized archive, it's returned as-is.
+pub fn extract_entry(data: &[u8], matches_name: impl Fn(&str) -> bool) -> Result<(Vec<u8>, bool), ArchiveError> {
+    match detect_format(data) {
+        ArchiveFormat::Zip => extract_from_zip(data, &matches_name),
+        ArchiveFormat::TarGz => extract_from_tar(flate2::read::GzDecoder::new(data), &matches_name),
+        ArchiveFormat::TarXz => extract_from_tar(xz2::read::XzDecoder::new(data), &matches_name),
+        ArchiveFormat::Raw => Ok((data.to_vec(), true)),
+    }
+}
+
+fn extract_from_tar(reader: impl Read, matches_name: &impl Fn(&str) -> bool) -> Result<(Vec<u8>, bool), ArchiveError> {
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive.entries().map_err(|e| ArchiveError::Read(e.to_string()))?;
+
+    for entry in entries {
+        let mut entry = entry.map_err(|e| ArchiveError::Read(e.to_string()))?;
+        let path = entry.path().map_err(|e| ArchiveError::Read(e.to_string()))?;
+        let name = path.to_string_lossy().to_string();
+
+        if matches_name(&name) {
+            let mode = entry.header().mode().unwrap_or(0);
+            let executable = mode & 0o111 != 0;
+            let mut buf = Vec::new();
+            entry.read_to_end(&mut buf).map_err(|e| ArchiveError::Read(e.to_string()))?;
+            return Ok((buf, executable));
+        }
+    }
+
+    Err(ArchiveError::EntryNotFound)
+}
+
+fn extract_from_zip(data: &[u8], matches_name: &impl Fn(&str) -> bool) -> Result<(Vec<u8>, bool), ArchiveError> {
+    let cursor = std::io::Cursor::new(data);
+    let mut archive = zip::ZipArchive::new(cursor).map_err(|e| ArchiveError::Read(e.to_string()))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| ArchiveError::Read(e.to_string()))?;
+        let name = entry.name().to_string();
+
+        if matches_name(&name) {
+            let executable = entry.unix_mode().map(|mode| mode & 0o111 != 0).unwrap_or(false);
+            let mut buf = Vec::new();
+            entry.read_to_end(&mut buf).map_err(|e| ArchiveError::Read(e.to_string()))?;
+            return Ok((buf, executable));
+        }
+    }
+
+    Err(ArchiveError::EntryNotFound)
+}
@@ -0,0 +1,88 @@
+//! At-rest encryption for auth files.
+//!
+//! `create_auth_file`/`scan_auth_files_direct` used to read and write
+//! `access_token`/`refresh_token` as plaintext JSON under the auth dir.
+//! Unlike [`crate::vault`], which derives its key from a user passphrase and
+//! requires an explicit unlock step, auth files need to be readable by the
+//! proxy at any time, so this module generates a random 256-bit key the
+//! first time it's needed and stores it in the OS keychain via
+//! [`crate::credentials`]. Encryption/decryption is then transparent: no
+//! unlock, just "is the keychain available".
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use rand::RngCore;
+use thiserror::Error;
+
+const NONCE_LEN: usize = 12;
+const KEYCHAIN_KEY: &str = "auth-files-master-key";
+
+#[derive(Error, Debug)]
+pub enum AuthCryptoError {
+    #[error("keychain error: {0}")]
+    Keychain(String),
+    #[error("crypto error: {0}")]
+    Crypto(String),
+}
+
+/// Load the master key from the keychain, generating and storing a fresh
+/// one on first use.
+fn master_key() -> Result<[u8; 32], AuthCryptoError> {
+    match crate::credentials::get_credential(KEYCHAIN_KEY) {
+        Ok(encoded) => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| AuthCryptoError::Crypto(e.to_string()))?;
+            bytes
+                .try_into()
+                .map_err(|_| AuthCryptoError::Crypto("stored master key has the wrong length".to_string()))
+        }
+        Err(_) => {
+            let mut key = [0u8; 32];
+            OsRng.fill_bytes(&mut key);
+            let encoded = base64::engine::general_purpose::STANDARD.encode(key);
+            crate::credentials::store_credential(KEYCHAIN_KEY, &encoded)
+                .map_err(|e| AuthCryptoError::Keychain(e.to_string()))?;
+            Ok(key)
+        }
+    }
+}
+
+/// Encrypt `plaintext`, returning `base64(nonce || ciphertext)`.
+pub fn encrypt(plaintext: &str) -> Result<String, AuthCryptoError> {
+    let key = master_key()?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext.as_bytes())
+        .map_err(|e| AuthCryptoError::Crypto(e.to_string()))?;
+
+    let mut combined = nonce_bytes.to_vec();
+    combined.extend_from_slice(&ciphertext);
+    Ok(base64::engine::general_purpose::STANDARD.encode(combined))
+}
+
+/// Decrypt a value produced by [`encrypt`].
+pub fn decrypt(encoded: &str) -> Result<String, AuthCryptoError> {
+    let key = master_key()?;
+    let combined = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| AuthCryptoError::Crypto(e.to_string()))?;
+
+    if combined.len() < NONCE_LEN {
+        return Err(AuthCryptoError::Crypto("ciphertext too short".to_string()));
+    }
+    let (nonce_bytes, ciphertext) = combined.split_at(NONCE_LEN);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|e| AuthCryptoError::Crypto(e.to_string()))?;
+
+    String::from_utf8(plaintext).map_err(|e| AuthCryptoError::Crypto(e.to_string()))
+}
@@ -0,0 +1,281 @@
+//! Standalone CLI companion to the Zest GUI.
+//!
+//! Talks to an already-running GUI over the one-shot IPC link in
+//! `zest_tauri_lib::ipc`. When the GUI isn't running (no server file, or
+//! the connection fails), `apply`/`remove`/`env` fall back to performing
+//! the same filesystem operations directly, since those are pure; `start`,
+//! `stop`, and `status` have nothing to fall back to and just report that
+//! the app isn't running.
+
+use clap::{CommandFactory, Parser, Subcommand};
+use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
+use zest_tauri_lib::ipc::{self, IpcRequest, IpcResponse};
+use zest_tauri_lib::shell_profile::{self, ShellType};
+use zest_tauri_lib::{agent_registry, paths, settings};
+
+#[derive(Parser)]
+#[command(name = "zest", about = "CLI companion to the Zest app")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start the proxy
+    Start,
+    /// Stop the proxy
+    Stop,
+    /// Print the proxy's running state and port
+    Status,
+    /// Print the env command for an agent (eval "$(zest env <agent>)")
+    Env {
+        agent: String,
+        #[arg(long)]
+        api_key: Option<String>,
+    },
+    /// Point an agent at the proxy by editing its shell profile
+    Apply {
+        agent: String,
+        #[arg(long)]
+        shell: Option<String>,
+        #[arg(long)]
+        api_key: Option<String>,
+    },
+    /// Undo `apply` for an agent
+    Remove {
+        agent: String,
+        #[arg(long)]
+        shell: Option<String>,
+    },
+    /// Run a single command with the proxy env vars set, without touching
+    /// any shell profile: `zest run <agent> -- <cmd...>`
+    Run {
+        agent: String,
+        #[arg(long)]
+        api_key: Option<String>,
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Generate (or install) shell completions
+    Completions {
+        #[arg(long)]
+        install: bool,
+        #[arg(long)]
+        shell: Option<String>,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Start => cmd_toggle(IpcRequest::Start),
+        Command::Stop => cmd_toggle(IpcRequest::Stop),
+        Command::Status => cmd_status(),
+        Command::Env { agent, api_key } => cmd_env(&agent, api_key),
+        Command::Apply { agent, shell, api_key } => cmd_apply(&agent, shell, api_key),
+        Command::Remove { agent, shell } => cmd_remove(&agent, shell),
+        Command::Run { agent, api_key, command } => cmd_run(&agent, api_key, command),
+        Command::Completions { install, shell } => cmd_completions(install, shell),
+    };
+
+    if let Err(e) = result {
+        eprintln!("zest: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn cmd_toggle(request: IpcRequest) -> Result<(), String> {
+    match send(request)? {
+        IpcResponse::Status(status) => {
+            print_status(&status);
+            Ok(())
+        }
+        IpcResponse::Error(e) => Err(e),
+        _ => Err("unexpected response from Zest".to_string()),
+    }
+}
+
+fn cmd_status() -> Result<(), String> {
+    match send(IpcRequest::Status) {
+        Ok(IpcResponse::Status(status)) => {
+            print_status(&status);
+            Ok(())
+        }
+        Ok(IpcResponse::Error(e)) => Err(e),
+        Ok(_) => Err("unexpected response from Zest".to_string()),
+        Err(_) => {
+            println!("Zest is not running");
+            Ok(())
+        }
+    }
+}
+
+fn print_status(status: &zest_tauri_lib::models::ProxyStatus) {
+    if status.running {
+        println!("running on port {}", status.port);
+    } else {
+        println!("stopped");
+    }
+}
+
+fn cmd_env(agent: &str, api_key: Option<String>) -> Result<(), String> {
+    agent_registry::find(agent).map_err(|e| e.to_string())?;
+
+    match send(IpcRequest::Env { agent: agent.to_string(), api_key: api_key.clone() }) {
+        Ok(IpcResponse::Env(cmd)) => {
+            println!("{}", cmd);
+            Ok(())
+        }
+        Ok(IpcResponse::Error(e)) => Err(e),
+        Ok(_) => Err("unexpected response from Zest".to_string()),
+        Err(_) => {
+            let port = fallback_port();
+            let cmd = ipc::env_command(agent, api_key.as_deref(), port)?;
+            println!("{}", cmd);
+            Ok(())
+        }
+    }
+}
+
+fn cmd_apply(agent: &str, shell: Option<String>, api_key: Option<String>) -> Result<(), String> {
+    agent_registry::find(agent).map_err(|e| e.to_string())?;
+
+    match send(IpcRequest::ApplyAgent {
+        agent: agent.to_string(),
+        shell: shell.clone(),
+        api_key: api_key.clone(),
+    }) {
+        Ok(IpcResponse::Ok) => Ok(()),
+        Ok(IpcResponse::Error(e)) => Err(e),
+        Ok(_) => Err("unexpected response from Zest".to_string()),
+        Err(_) => {
+            eprintln!("zest: Zest app isn't running, editing the shell profile directly");
+            let port = fallback_port();
+            ipc::apply_agent(agent, shell.as_deref(), api_key.as_deref(), port)
+        }
+    }
+}
+
+fn cmd_remove(agent: &str, shell: Option<String>) -> Result<(), String> {
+    agent_registry::find(agent).map_err(|e| e.to_string())?;
+
+    match send(IpcRequest::RemoveAgent { agent: agent.to_string(), shell: shell.clone() }) {
+        Ok(IpcResponse::Ok) => Ok(()),
+        Ok(IpcResponse::Error(e)) => Err(e),
+        Ok(_) => Err("unexpected response from Zest".to_string()),
+        Err(_) => {
+            eprintln!("zest: Zest app isn't running, editing the shell profile directly");
+            ipc::remove_agent(agent, shell.as_deref())
+        }
+    }
+}
+
+/// Run `command` with the agent's proxy env vars set, forwarding its exit
+/// code. Asks a running GUI for the live port, falling back to the
+/// configured one so this works with no GUI running at all.
+fn cmd_run(agent: &str, api_key: Option<String>, command: Vec<String>) -> Result<(), String> {
+    let manifest = agent_registry::find(agent).map_err(|e| e.to_string())?;
+    if command.is_empty() {
+        return Err("no command given; usage: zest run <agent> -- <cmd...>".to_string());
+    }
+
+    let port = match send(IpcRequest::Status) {
+        Ok(IpcResponse::Status(status)) => status.port,
+        _ => fallback_port(),
+    };
+
+    let command: Vec<std::ffi::OsString> = command.into_iter().map(Into::into).collect();
+    let code = zest_tauri_lib::terminal_launch::spawn_with_env(&manifest, port, api_key.as_deref(), &command)
+        .map_err(|e| e.to_string())?;
+    std::process::exit(code);
+}
+
+/// Map this crate's `ShellType` onto the `clap_complete::Shell` it
+/// corresponds to. `Cmd` has no completion support in `clap_complete`.
+fn to_clap_shell(shell: ShellType) -> Result<clap_complete::Shell, String> {
+    match shell {
+        ShellType::Zsh => Ok(clap_complete::Shell::Zsh),
+        ShellType::Bash => Ok(clap_complete::Shell::Bash),
+        ShellType::Fish => Ok(clap_complete::Shell::Fish),
+        ShellType::Powershell => Ok(clap_complete::Shell::PowerShell),
+        ShellType::Cmd => Err("cmd has no completion support".to_string()),
+    }
+}
+
+fn cmd_completions(install: bool, shell: Option<String>) -> Result<(), String> {
+    let shell_type = match shell {
+        Some(s) => shell_profile::parse_shell_type(&s)?,
+        None => shell_profile::detect_shell(),
+    };
+    let clap_shell = to_clap_shell(shell_type)?;
+    let mut cmd = Cli::command();
+
+    if !install {
+        clap_complete::generate(clap_shell, &mut cmd, "zest", &mut std::io::stdout());
+        return Ok(());
+    }
+
+    let mut script = Vec::new();
+    clap_complete::generate(clap_shell, &mut cmd, "zest", &mut script);
+    let script = String::from_utf8(script).map_err(|e| e.to_string())?;
+    let home = dirs::home_dir().ok_or("could not find home directory")?;
+
+    match shell_type {
+        ShellType::Fish => write_completion_file(&home.join(".config/fish/completions/zest.fish"), &script),
+        ShellType::Zsh => write_completion_file(&home.join(".zsh/completions/_zest"), &script),
+        ShellType::Bash => {
+            let completion_path = home.join(".zest/completions/zest.bash");
+            write_completion_file(&completion_path, &script)?;
+            let source_line = format!("source \"{}\"\n", completion_path.display());
+            shell_profile::add_to_profile(ShellType::Bash, "zest completions", &source_line)
+                .map_err(|e| e.to_string())
+        }
+        ShellType::Powershell => {
+            let profile_path = ShellType::Powershell.profile_path();
+            if let Some(parent) = profile_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            use std::io::Write;
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&profile_path)
+                .map_err(|e| e.to_string())?;
+            file.write_all(script.as_bytes()).map_err(|e| e.to_string())
+        }
+        ShellType::Cmd => unreachable!("to_clap_shell already rejected Cmd"),
+    }
+}
+
+fn write_completion_file(path: &std::path::Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, content).map_err(|e| e.to_string())
+}
+
+fn fallback_port() -> u16 {
+    settings::load_settings().map(|s| s.port).unwrap_or(8317)
+}
+
+/// Connect to the GUI's one-shot IPC server (if it's running) and block for
+/// a single reply.
+fn send(request: IpcRequest) -> Result<IpcResponse, String> {
+    let server_name = std::fs::read_to_string(paths::ipc_server_file())
+        .map_err(|_| "no Zest instance found".to_string())?;
+
+    let main_sender: IpcSender<(IpcRequest, IpcSender<IpcResponse>)> =
+        IpcSender::connect(server_name.trim().to_string()).map_err(|e| e.to_string())?;
+
+    let (reply_server, reply_name) =
+        IpcOneShotServer::<IpcResponse>::new().map_err(|e| e.to_string())?;
+
+    main_sender
+        .send((request, IpcSender::connect(reply_name).map_err(|e| e.to_string())?))
+        .map_err(|e| e.to_string())?;
+
+    let (_rx, response) = reply_server.accept().map_err(|e| e.to_string())?;
+    Ok(response)
+}
@@ -0,0 +1,219 @@
+//! Native clipboard access.
+//!
+//! Backed by `arboard` on all platforms. Under Wayland (no X server) the
+//! old subprocess tools (`xclip`/`xsel`) silently fail, so when arboard
+//! fails, `WAYLAND_DISPLAY` is set and `wl-copy`/`wl-paste` are available,
+//! those are tried next; the legacy subprocess commands remain as a last-resort
+//! fallback for whenever the native backend itself returns an error (e.g.
+//! missing X11/Wayland client libs on a minimal Linux install).
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClipboardError {
+    #[error("clipboard backend unavailable: {0}")]
+    Unavailable(String),
+    #[error("failed to decode image: {0}")]
+    ImageDecode(String),
+}
+
+fn is_wayland() -> bool {
+    std::env::var("WAYLAND_DISPLAY").is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn command_exists(name: &str) -> bool {
+    Command::new("which")
+        .arg(name)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Copy `text` to the system clipboard.
+pub fn copy_text(text: &str) -> Result<(), ClipboardError> {
+    match arboard::Clipboard::new().and_then(|mut cb| cb.set_text(text.to_string())) {
+        Ok(()) => return Ok(()),
+        Err(e) => log::warn!("arboard clipboard write failed, falling back: {}", e),
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if is_wayland() && command_exists("wl-copy") {
+            if write_via_subprocess("wl-copy", &[], text).is_ok() {
+                return Ok(());
+            }
+        }
+    }
+
+    legacy_copy_text(text)
+}
+
+/// Read the current text contents of the system clipboard.
+pub fn read_text() -> Result<String, ClipboardError> {
+    match arboard::Clipboard::new().and_then(|mut cb| cb.get_text()) {
+        Ok(text) => return Ok(text),
+        Err(e) => log::warn!("arboard clipboard read failed, falling back: {}", e),
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if is_wayland() && command_exists("wl-paste") {
+            if let Ok(text) = read_via_subprocess("wl-paste", &["--no-newline"]) {
+                return Ok(text);
+            }
+        }
+    }
+
+    legacy_read_text()
+}
+
+/// Decode `png_bytes` and place the raw image on the clipboard, so the UI
+/// can copy generated QR codes / screenshots as actual image data rather
+/// than a file path.
+pub fn copy_image_png(png_bytes: &[u8]) -> Result<(), ClipboardError> {
+    let decoded = image::load_from_memory(png_bytes)
+        .map_err(|e| ClipboardError::ImageDecode(e.to_string()))?
+        .into_rgba8();
+    let (width, height) = decoded.dimensions();
+
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+    clipboard
+        .set_image(arboard::ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: std::borrow::Cow::Owned(decoded.into_raw()),
+        })
+        .map_err(|e| ClipboardError::Unavailable(e.to_string()))
+}
+
+/// Pipe `text` into `command`'s stdin (used for `wl-copy`).
+#[cfg(target_os = "linux")]
+fn write_via_subprocess(command: &str, args: &[&str], text: &str) -> Result<(), ClipboardError> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(text.as_bytes()).map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+    }
+
+    child.wait().map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+    Ok(())
+}
+
+/// Run `command` and capture its stdout (used for `wl-paste`).
+#[cfg(target_os = "linux")]
+fn read_via_subprocess(command: &str, args: &[&str]) -> Result<String, ClipboardError> {
+    let output = Command::new(command)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(ClipboardError::Unavailable(format!("{} exited with {}", command, output.status)));
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| ClipboardError::Unavailable(e.to_string()))
+}
+
+/// The subprocess-based clipboard write this module replaces as the
+/// default, kept as a last resort for when arboard (and, on Linux, the
+/// Wayland tools) can't reach a clipboard backend at all.
+fn legacy_copy_text(text: &str) -> Result<(), ClipboardError> {
+    #[cfg(target_os = "macos")]
+    {
+        let mut child = Command::new("pbcopy")
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin.write_all(text.as_bytes()).map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+        }
+
+        child.wait().map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+        return Ok(());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        let mut child = Command::new("cmd")
+            .args(["/C", "clip"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .creation_flags(CREATE_NO_WINDOW)
+            .spawn()
+            .map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin.write_all(text.as_bytes()).map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+        }
+
+        child.wait().map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if write_via_subprocess("xclip", &["-selection", "clipboard"], text).is_ok() {
+            return Ok(());
+        }
+
+        return write_via_subprocess("xsel", &["--clipboard", "--input"], text);
+    }
+
+    #[allow(unreachable_code)]
+    Err(ClipboardError::Unavailable("no clipboard backend available on this platform".to_string()))
+}
+
+/// Last-resort subprocess-based clipboard read, mirroring `legacy_copy_text`.
+fn legacy_read_text() -> Result<String, ClipboardError> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("pbpaste")
+            .output()
+            .map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+        return String::from_utf8(output.stdout).map_err(|e| ClipboardError::Unavailable(e.to_string()));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-Command", "Get-Clipboard"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| ClipboardError::Unavailable(e.to_string()))?;
+        return String::from_utf8(output.stdout).map_err(|e| ClipboardError::Unavailable(e.to_string()));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(text) = read_via_subprocess("xclip", &["-selection", "clipboard", "-o"]) {
+            return Ok(text);
+        }
+
+        return read_via_subprocess("xsel", &["--clipboard", "--output"]);
+    }
+
+    #[allow(unreachable_code)]
+    Err(ClipboardError::Unavailable("no clipboard backend available on this platform".to_string()))
+}
@@ -2,12 +2,15 @@
 //!
 //! These are the commands exposed to the frontend via Tauri's invoke system.
 
-use crate::models::{AuthFile, AuthFileModel, AuthFileModelsResponse, ProxyStatus, QuotaInfo, OAuthFlowResult};
+use crate::agent_registry::{self, AgentManifest};
+use crate::environment::HostEnvironment;
+use crate::management_client::ManagementApiClient;
+use crate::models::{AuthFile, AuthFileModel, ProxyStatus, QuotaInfo, OAuthFlowResult};
 use crate::proxy::{self, ProxyState};
 use crate::settings::{self, AppSettings, SettingsState};
 use crate::credentials;
-use crate::shell_profile::{self, ShellType, CLIAgent};
-use tauri::State;
+use crate::shell_profile::{self, ShellType};
+use tauri::{AppHandle, Emitter, State};
 use std::path::Path;
 
 // ============================================================================
@@ -15,30 +18,99 @@ use std::path::Path;
 // ============================================================================
 
 #[tauri::command]
-pub async fn start_proxy(state: State<'_, ProxyState>) -> Result<ProxyStatus, String> {
-    proxy::start_proxy(&state.inner)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn start_proxy(
+    app: AppHandle,
+    state: State<'_, ProxyState>,
+    settings_state: State<'_, SettingsState>,
+) -> Result<ProxyStatus, String> {
+    let (settings_proxy, ca_bundle_path) = {
+        let settings = settings_state.inner.lock().await;
+        (settings.outbound_proxy_url.clone(), settings.ca_bundle_path.clone())
+    };
+    let outbound_proxy = proxy::resolve_outbound_proxy(settings_proxy.as_deref());
+    let status = proxy::start_proxy(
+        &app,
+        &state.inner,
+        outbound_proxy.as_deref(),
+        ca_bundle_path.as_deref().map(Path::new),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Err(e) = crate::tray::rebuild_tray_menu(&app, status.running, status.port) {
+        log::warn!("failed to rebuild tray menu: {}", e);
+    }
+    Ok(status)
 }
 
 #[tauri::command]
-pub async fn stop_proxy(state: State<'_, ProxyState>) -> Result<ProxyStatus, String> {
-    proxy::stop_proxy(&state.inner)
+pub async fn stop_proxy(app: AppHandle, state: State<'_, ProxyState>) -> Result<ProxyStatus, String> {
+    let status = proxy::stop_proxy(&state.inner)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = crate::tray::rebuild_tray_menu(&app, status.running, status.port) {
+        log::warn!("failed to rebuild tray menu: {}", e);
+    }
+    Ok(status)
 }
 
 #[tauri::command]
 pub async fn get_proxy_status(state: State<'_, ProxyState>) -> Result<ProxyStatus, String> {
     let inner = state.inner.lock().await;
-    Ok(inner.status.clone())
+    let mut status = inner.status.clone();
+    if let Some(started_at) = inner.started_at {
+        status.uptime_seconds = Some(started_at.elapsed().as_secs());
+    }
+    Ok(status)
 }
 
+/// Snapshot of the managed process's captured stdout/stderr since the last
+/// `start_proxy`, oldest first. Live updates also arrive as `proxy-log`
+/// events.
 #[tauri::command]
-pub async fn install_proxy_binary(state: State<'_, ProxyState>) -> Result<String, String> {
-    proxy::install_binary(&state.inner)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn get_proxy_logs(state: State<'_, ProxyState>) -> Result<Vec<proxy::ProxyLogLine>, String> {
+    Ok(proxy::get_proxy_logs(&state.inner).await)
+}
+
+/// Install or update the proxy binary via the background job queue
+/// (resumable download, checksum verification, retry with backoff), and
+/// wait for it to finish so existing callers keep getting a version string
+/// back. Use `get_install_jobs`/`cancel_install_job` for granular progress.
+#[tauri::command]
+pub async fn install_proxy_binary(
+    app: AppHandle,
+    state: State<'_, ProxyState>,
+    settings_state: State<'_, SettingsState>,
+) -> Result<String, String> {
+    let (verify_checksums, settings_proxy) = {
+        let settings = settings_state.inner.lock().await;
+        (settings.verify_checksums, settings.outbound_proxy_url.clone())
+    };
+    let outbound_proxy = proxy::resolve_outbound_proxy(settings_proxy.as_deref());
+    let job_id = crate::install_jobs::start(app, state.inner().clone(), verify_checksums, outbound_proxy);
+
+    loop {
+        let jobs = crate::install_jobs::get_install_jobs();
+        if let Some(job) = jobs.into_iter().find(|j| j.id == job_id) {
+            match job.state {
+                crate::install_jobs::InstallJobState::Done { version } => return Ok(version),
+                crate::install_jobs::InstallJobState::Failed { error } => return Err(error),
+                _ => {}
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+}
+
+/// Snapshot of every tracked install job.
+#[tauri::command]
+pub fn get_install_jobs() -> Vec<crate::install_jobs::InstallJob> {
+    crate::install_jobs::get_install_jobs()
+}
+
+/// Cancel a running or queued install job.
+#[tauri::command]
+pub fn cancel_install_job(job_id: String) {
+    crate::install_jobs::cancel_install_job(&job_id)
 }
 
 #[tauri::command]
@@ -47,6 +119,79 @@ pub async fn get_proxy_version(state: State<'_, ProxyState>) -> Result<Option<St
     Ok(inner.status.version.clone())
 }
 
+/// Check the app and proxy versions are compatible, so a mismatch surfaces
+/// as a clear upgrade prompt instead of an opaque JSON-parse error deep
+/// inside `fetch_logs`/`fetch_usage`.
+#[tauri::command]
+pub async fn check_proxy_compatibility(
+    state: State<'_, ProxyState>,
+) -> Result<crate::models::CompatibilityReport, String> {
+    use crate::models::{CompatibilityReport, CompatibilityStatus};
+
+    let inner = state.inner.lock().await;
+
+    if !inner.status.running {
+        return Err("Proxy is not running".to_string());
+    }
+
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
+    let info = client.version().await.map_err(|e| e.to_string())?;
+
+    let status = if let Some(min_client) = &info.min_client_version {
+        if version_compare(proxy::CLIENT_VERSION, min_client) == std::cmp::Ordering::Less {
+            CompatibilityStatus::ClientTooOld
+        } else if version_compare(&info.version, proxy::MIN_SUPPORTED_PROXY_VERSION) == std::cmp::Ordering::Less {
+            CompatibilityStatus::ProxyTooOld
+        } else {
+            CompatibilityStatus::Ok
+        }
+    } else if version_compare(&info.version, proxy::MIN_SUPPORTED_PROXY_VERSION) == std::cmp::Ordering::Less {
+        CompatibilityStatus::ProxyTooOld
+    } else {
+        CompatibilityStatus::Ok
+    };
+
+    Ok(CompatibilityReport {
+        proxy_version: info.version,
+        client_version: proxy::CLIENT_VERSION.to_string(),
+        status,
+    })
+}
+
+/// Compare the installed proxy binary's version against the latest
+/// `CLIProxyAPI` GitHub release, so the UI can show an "update available"
+/// badge next to the install/reinstall button. Returns `None` when already
+/// up to date, the binary isn't installed, or the running version is
+/// unknown.
+#[tauri::command]
+pub async fn check_for_proxy_binary_update(state: State<'_, ProxyState>) -> Result<Option<String>, String> {
+    let installed_version = state.inner.lock().await.status.version.clone();
+    let Some(installed_version) = installed_version else {
+        return Ok(None);
+    };
+
+    let client = reqwest::Client::new();
+    let release_url = format!("https://api.github.com/repos/{}/releases/latest", proxy::GITHUB_REPO);
+    let release: crate::models::GitHubRelease = client
+        .get(&release_url)
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "Zest/1.0")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let latest_version = release.tag_name.trim_start_matches('v');
+    if version_compare(latest_version, &installed_version) == std::cmp::Ordering::Greater {
+        Ok(Some(latest_version.to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
 /// Returns the management API key used by the proxy
 /// This is needed for the frontend to authenticate with the proxy's /models endpoint
 #[tauri::command]
@@ -182,6 +327,62 @@ pub async fn save_settings(
     settings::save_settings(&new_settings).map_err(|e| e.to_string())
 }
 
+/// Returns the JSON Schema for `AppSettings` so the frontend can render a
+/// typed settings form and validate a hand-edited `settings.json`.
+#[tauri::command]
+pub fn get_settings_schema() -> serde_json::Value {
+    settings::settings_schema()
+}
+
+// ============================================================================
+// Settings Profile Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn list_settings_profiles() -> Vec<String> {
+    settings::list_profiles()
+}
+
+#[tauri::command]
+pub fn get_active_settings_profile() -> Option<String> {
+    settings::active_profile()
+}
+
+#[tauri::command]
+pub async fn save_settings_profile(
+    state: State<'_, SettingsState>,
+    name: String,
+) -> Result<(), String> {
+    let settings = state.inner.lock().await;
+    settings::save_profile(&name, &settings).map_err(|e| e.to_string())
+}
+
+/// Switch the active profile: loads its settings, swaps them into the
+/// running `SettingsState`, and emits `settings-profile-changed` so the tray
+/// and menu-bar UI can react (e.g. rebind the port).
+#[tauri::command]
+pub async fn activate_settings_profile(
+    app: AppHandle,
+    state: State<'_, SettingsState>,
+    name: String,
+) -> Result<AppSettings, String> {
+    let profile = settings::activate_profile(&name).map_err(|e| e.to_string())?;
+
+    {
+        let mut inner = state.inner.lock().await;
+        *inner = profile.clone();
+    }
+
+    let _ = app.emit("settings-profile-changed", &profile);
+
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_settings_profile(name: String) -> Result<(), String> {
+    settings::delete_profile(&name).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_port(state: State<'_, ProxyState>) -> Result<u16, String> {
     let inner = state.inner.lock().await;
@@ -233,18 +434,10 @@ pub async fn delete_auth_file(
         return Err("Proxy is not running".to_string());
     }
 
-    // Use query param ?name= as in Swift ManagementAPIClient.swift
-    let url = format!("{}/auth-files?name={}", inner.management_url(), file_name);
-    let client = reqwest::Client::new();
-
-    client
-        .delete(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    Ok(())
+    client.delete_auth_file(&file_name).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -259,22 +452,13 @@ pub async fn toggle_auth_file(
         return Err("Proxy is not running".to_string());
     }
 
-    let url = format!("{}/auth-files/{}/toggle", inner.management_url(), file_id);
-    let client = reqwest::Client::new();
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .json(&serde_json::json!({ "disabled": disabled }))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(())
+    client.toggle_auth_file(&file_id, disabled).await.map_err(|e| e.to_string())
 }
 
 /// Fetch models available for a specific auth file
-/// This mirrors Swift's fetchAuthFileModels(name:) in ManagementAPIClient.swift
 #[tauri::command]
 pub async fn fetch_auth_file_models(
     state: State<'_, ProxyState>,
@@ -286,39 +470,10 @@ pub async fn fetch_auth_file_models(
         return Err("Proxy is not running".to_string());
     }
 
-    let encoded = urlencoding::encode(&auth_file_name);
-    let url = format!("{}/auth-files/models?name={}", inner.management_url(), encoded);
-    let client = reqwest::Client::new();
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    log::debug!("Fetching models for auth file: {} from {}", auth_file_name, url);
-
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-        .map_err(|e| {
-            log::error!("Failed to fetch auth file models: {}", e);
-            e.to_string()
-        })?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        log::error!("Auth file models request failed with status: {}", status);
-        return Err(format!("Request failed with status: {}", status));
-    }
-
-    let response_data: AuthFileModelsResponse = response
-        .json()
-        .await
-        .map_err(|e| {
-            log::error!("Failed to parse auth file models response: {}", e);
-            e.to_string()
-        })?;
-
-    log::debug!("Fetched {} models for auth file {}", response_data.models.len(), auth_file_name);
-
-    Ok(response_data.models)
+    client.auth_file_models(&auth_file_name).await.map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -337,18 +492,10 @@ pub async fn fetch_quota(
         return Err("Proxy is not running".to_string());
     }
 
-    let url = format!("{}/quota/{}/{}", inner.management_url(), provider, account);
-    let client = reqwest::Client::new();
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .json::<QuotaInfo>()
-        .await
-        .map_err(|e| e.to_string())
+    client.quota(&provider, &account).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -359,21 +506,10 @@ pub async fn fetch_all_quotas(state: State<'_, ProxyState>) -> Result<Vec<QuotaI
         return Ok(Vec::new());
     }
 
-    let url = format!("{}/quotas", inner.management_url());
-    let client = reqwest::Client::new();
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if response.status().is_success() {
-        response.json::<Vec<QuotaInfo>>().await.map_err(|e| e.to_string())
-    } else {
-        Ok(Vec::new())
-    }
+    Ok(client.all_quotas().await.unwrap_or_default())
 }
 
 // ============================================================================
@@ -382,29 +518,40 @@ pub async fn fetch_all_quotas(state: State<'_, ProxyState>) -> Result<Vec<QuotaI
 
 #[tauri::command]
 pub async fn get_api_keys(state: State<'_, ProxyState>) -> Result<Vec<String>, String> {
-    proxy::fetch_api_keys(&state.inner)
+    let keys = proxy::fetch_api_keys(&state.inner)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    // Keys whose scope has expired are hidden rather than deleted, so a
+    // re-activation (e.g. extending the expiry) doesn't require re-adding
+    // the key to the proxy.
+    let scopes = crate::policy::load_registry()?;
+    let expired: std::collections::HashSet<_> = scopes
+        .iter()
+        .filter(|r| r.scope.is_expired())
+        .map(|r| r.key.clone())
+        .collect();
+
+    Ok(keys.into_iter().filter(|k| !expired.contains(k)).collect())
 }
 
 #[tauri::command]
-pub async fn add_api_key(state: State<'_, ProxyState>, key: String) -> Result<(), String> {
+pub async fn add_api_key(
+    state: State<'_, ProxyState>,
+    key: String,
+    scope: Option<crate::policy::KeyScope>,
+) -> Result<(), String> {
     let inner = state.inner.lock().await;
 
     if !inner.status.running {
         return Err("Proxy is not running".to_string());
     }
 
-    let url = format!("{}/api-keys", inner.management_url());
-    let client = reqwest::Client::new();
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .json(&serde_json::json!({ "key": key }))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    client.add_api_key(&key).await.map_err(|e| e.to_string())?;
+    crate::policy::upsert_scope(&key, scope.unwrap_or_default())?;
 
     Ok(())
 }
@@ -417,111 +564,76 @@ pub async fn delete_api_key(state: State<'_, ProxyState>, key: String) -> Result
         return Err("Proxy is not running".to_string());
     }
 
-    let url = format!("{}/api-keys/{}", inner.management_url(), key);
-    let client = reqwest::Client::new();
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    client
-        .delete(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    client.delete_api_key(&key).await.map_err(|e| e.to_string())?;
+    crate::policy::remove_scope(&key)?;
 
     Ok(())
 }
 
+/// Evaluate a key's policy chain for a given provider/model before a
+/// request is allowed through.
+#[tauri::command]
+pub fn check_key_authorized(key: String, provider: String, model: String) -> Result<bool, String> {
+    crate::policy::is_authorized(&key, &provider, &model)
+}
+
 // ============================================================================
 // Logs Commands
 // ============================================================================
 
-/// Fetch logs from the proxy management API
+/// Fetch logs from the proxy management API. Any failure (proxy not
+/// running, connection error, malformed response) yields an empty result
+/// rather than an error, since the frontend treats "no logs yet" and "could
+/// not reach the proxy" the same way.
 #[tauri::command]
 pub async fn fetch_logs(
     state: State<'_, ProxyState>,
     after_timestamp: Option<i64>,
 ) -> Result<crate::models::LogsResponse, String> {
+    let empty = || crate::models::LogsResponse {
+        lines: Some(vec![]),
+        line_count: Some(0),
+        latest_timestamp: None,
+    };
+
     let inner = state.inner.lock().await;
 
     if !inner.status.running {
         log::debug!("Proxy not running, returning empty logs");
-        return Ok(crate::models::LogsResponse {
-            lines: Some(vec![]),
-            line_count: Some(0),
-            latest_timestamp: None,
-        });
+        return Ok(empty());
     }
 
-    let mut url = format!("{}/logs", inner.management_url());
-    if let Some(after) = after_timestamp {
-        url = format!("{}?after={}", url, after);
-    }
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    log::debug!("Fetching logs from: {}", url);
-
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_default();
-
-    let response = match client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-    {
-        Ok(r) => r,
+    match client.logs(after_timestamp).await {
+        Ok(logs) => Ok(logs),
         Err(e) => {
             log::warn!("Failed to fetch logs from proxy: {}", e);
-            return Ok(crate::models::LogsResponse {
-                lines: Some(vec![]),
-                line_count: Some(0),
-                latest_timestamp: None,
-            });
+            Ok(empty())
         }
-    };
-
-    let status = response.status();
-    log::debug!("Logs response status: {}", status);
-
-    if status.is_success() {
-        // Get raw text first for debugging
-        let raw_text = match response.text().await {
-            Ok(text) => text,
-            Err(e) => {
-                log::warn!("Failed to read logs response body: {}", e);
-                return Ok(crate::models::LogsResponse {
-                    lines: Some(vec![]),
-                    line_count: Some(0),
-                    latest_timestamp: None,
-                });
-            }
-        };
+    }
+}
 
-        log::debug!("Logs raw response: {}", if raw_text.len() > 200 { &raw_text[..200] } else { &raw_text });
+/// Start streaming proxy logs as incremental `log-line` events, optionally
+/// filtered to a minimum severity. Returns a stream id for `stop_log_stream`.
+#[tauri::command]
+pub fn start_log_stream(
+    app: AppHandle,
+    state: State<'_, ProxyState>,
+    min_level: Option<crate::models::LogLevel>,
+) -> Result<String, String> {
+    Ok(crate::log_stream::start(app, state.inner().clone(), min_level))
+}
 
-        // Parse JSON
-        match serde_json::from_str::<crate::models::LogsResponse>(&raw_text) {
-            Ok(logs) => {
-                log::debug!("Parsed {} log lines", logs.lines.as_ref().map(|l| l.len()).unwrap_or(0));
-                Ok(logs)
-            }
-            Err(e) => {
-                log::warn!("Failed to parse logs response: {} - Raw: {}", e, &raw_text);
-                Ok(crate::models::LogsResponse {
-                    lines: Some(vec![]),
-                    line_count: Some(0),
-                    latest_timestamp: None,
-                })
-            }
-        }
-    } else {
-        log::warn!("Logs request failed with status: {}", status);
-        Ok(crate::models::LogsResponse {
-            lines: Some(vec![]),
-            line_count: Some(0),
-            latest_timestamp: None,
-        })
-    }
+/// Stop a log stream started with `start_log_stream`.
+#[tauri::command]
+pub fn stop_log_stream(stream_id: String) -> Result<(), String> {
+    crate::log_stream::stop(&stream_id);
+    Ok(())
 }
 
 /// Clear logs from the proxy
@@ -533,17 +645,10 @@ pub async fn clear_logs(state: State<'_, ProxyState>) -> Result<(), String> {
         return Ok(());
     }
 
-    let url = format!("{}/logs", inner.management_url());
-    let client = reqwest::Client::new();
-
-    client
-        .delete(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    Ok(())
+    client.clear_logs().await.map_err(|e| e.to_string())
 }
 
 /// Fetch usage data from the proxy (includes model information)
@@ -555,36 +660,13 @@ pub async fn fetch_usage(state: State<'_, ProxyState>) -> Result<serde_json::Val
         return Ok(serde_json::json!({}));
     }
 
-    let url = format!("{}/usage", inner.management_url());
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_default();
+    let client = ManagementApiClient::new(&inner);
+    drop(inner);
 
-    let response = match client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            log::warn!("Failed to fetch usage: {}", e);
-            return Ok(serde_json::json!({}));
-        }
-    };
-
-    if response.status().is_success() {
-        match response.json::<serde_json::Value>().await {
-            Ok(data) => Ok(data),
-            Err(e) => {
-                log::warn!("Failed to parse usage response: {}", e);
-                Ok(serde_json::json!({}))
-            }
-        }
-    } else {
-        Ok(serde_json::json!({}))
-    }
+    Ok(client.usage().await.unwrap_or_else(|e| {
+        log::warn!("Failed to fetch usage: {}", e);
+        serde_json::json!({})
+    }))
 }
 
 /// Fetch request history from the request-history.json file
@@ -640,23 +722,142 @@ pub fn clear_request_history() -> Result<(), String> {
     Ok(())
 }
 
+/// Render the current Prometheus metrics snapshot (the same data served on
+/// the `/metrics` HTTP endpoint), for display inside the app itself.
+#[tauri::command]
+pub fn get_metrics() -> String {
+    crate::metrics::render()
+}
+
+// ============================================================================
+// OIDC Commands (control panel SSO login, distinct from per-provider OAuth)
+// ============================================================================
+
+/// Start an OIDC login against `remote-management.oidc`, opening the
+/// identity provider's consent page in the default browser. Poll
+/// `finish_oidc_login` with the returned `state` once the browser redirects
+/// back.
+#[tauri::command]
+pub async fn start_oidc_login() -> Result<OAuthFlowResult, String> {
+    let config = crate::config_watcher::load_config(&proxy::ProxyStateInner::config_path())?;
+    let oidc = config
+        .remote_management
+        .oidc
+        .ok_or_else(|| "OIDC is not configured".to_string())?;
+    crate::oidc::start_oidc_login(&oidc).await.map_err(|e| e.to_string())
+}
+
+/// Exchange the authorization `code` the identity provider redirected back
+/// with, validate the ID token, and return the `NavigationPage`s its groups
+/// claim allows.
+#[tauri::command]
+pub async fn finish_oidc_login(oauth_state: String, code: String) -> Result<crate::models::OAuthStatusResponse, String> {
+    let config = crate::config_watcher::load_config(&proxy::ProxyStateInner::config_path())?;
+    let oidc = config
+        .remote_management
+        .oidc
+        .ok_or_else(|| "OIDC is not configured".to_string())?;
+    let allowed_pages = crate::oidc::finish_oidc_login(&oidc, &oauth_state, &code)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(crate::models::OAuthStatusResponse {
+        status: "completed".to_string(),
+        error: None,
+        allowed_pages: Some(allowed_pages),
+    })
+}
+
+// ============================================================================
+// Updater Commands
+// ============================================================================
+
+/// Check whether a newer Zest release is available. Returns `None` when
+/// already up to date.
+#[tauri::command]
+pub async fn check_for_update() -> Result<Option<crate::models::GitHubRelease>, String> {
+    crate::updater::check_for_update().await.map_err(|e| e.to_string())
+}
+
+/// Select the asset matching this platform from `release`, then download
+/// and install it, emitting `update-progress` events as
+/// `{"downloaded": u64, "total": u64}` so the About page can show a
+/// percentage. Requires an app restart to take effect.
+#[tauri::command]
+pub async fn apply_update(app: AppHandle, release: crate::models::GitHubRelease) -> Result<(), String> {
+    let asset = crate::updater::select_asset(&release).map_err(|e| e.to_string())?.clone();
+
+    crate::updater::apply_update(&release, &asset, |downloaded, total| {
+        let _ = app.emit("update-progress", serde_json::json!({ "downloaded": downloaded, "total": total }));
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Credentials Commands
 // ============================================================================
 
+/// Build the `CredentialBackend` the current settings select: the
+/// OS-native keychain, unless `onepassword` or `credential_process` is
+/// configured, in which case it supersedes the keychain entirely.
+async fn credential_backend(state: &State<'_, SettingsState>) -> credentials::CredentialBackend {
+    let settings = state.inner.lock().await;
+    if let Some(config) = &settings.onepassword {
+        return credentials::CredentialBackend::OnePassword(config.clone());
+    }
+    match &settings.credential_process {
+        Some(config) => credentials::CredentialBackend::Process(config.clone()),
+        None => credentials::CredentialBackend::Os,
+    }
+}
+
+#[tauri::command]
+pub async fn store_credential(key: String, value: String, state: State<'_, SettingsState>) -> Result<(), String> {
+    let encrypted = crate::vault::encrypt(&value).map_err(|e| e.to_string())?;
+    let backend = credential_backend(&state).await;
+    credentials::store_credential_with_backend(&key, &encrypted, &backend).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_credential(key: String, state: State<'_, SettingsState>) -> Result<String, String> {
+    if !crate::vault::is_unlocked() {
+        return Err(crate::vault::VaultError::Locked.to_string());
+    }
+    let backend = credential_backend(&state).await;
+    let encrypted = credentials::get_credential_with_backend(&key, &backend).map_err(|e| e.to_string())?;
+    crate::vault::decrypt(&encrypted).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_credential(key: String, state: State<'_, SettingsState>) -> Result<(), String> {
+    let backend = credential_backend(&state).await;
+    credentials::delete_credential_with_backend(&key, &backend).map_err(|e| e.to_string())
+}
+
+/// Set (or change) the master passphrase protecting the credential vault.
+/// Leaves the vault unlocked with the new key afterwards.
 #[tauri::command]
-pub fn store_credential(key: String, value: String) -> Result<(), String> {
-    credentials::store_credential(&key, &value).map_err(|e| e.to_string())
+pub fn set_master_passphrase(passphrase: String) -> Result<(), String> {
+    crate::vault::set_master_passphrase(&passphrase).map_err(|e| e.to_string())
 }
 
+/// Unlock the credential vault, holding the derived key in memory until
+/// `lock_vault` is called or the app exits.
 #[tauri::command]
-pub fn get_credential(key: String) -> Result<String, String> {
-    credentials::get_credential(&key).map_err(|e| e.to_string())
+pub fn unlock_vault(passphrase: String) -> Result<(), String> {
+    crate::vault::unlock_vault(&passphrase).map_err(|e| e.to_string())
 }
 
+/// Drop the derived vault key from memory.
 #[tauri::command]
-pub fn delete_credential(key: String) -> Result<(), String> {
-    credentials::delete_credential(&key).map_err(|e| e.to_string())
+pub fn lock_vault() -> Result<(), String> {
+    crate::vault::lock_vault().map_err(|e| e.to_string())
+}
+
+/// Whether the credential vault is currently unlocked.
+#[tauri::command]
+pub fn is_vault_unlocked() -> bool {
+    crate::vault::is_unlocked()
 }
 
 // ============================================================================
@@ -668,7 +869,7 @@ pub async fn start_oauth_flow(
     state: State<'_, ProxyState>,
     provider: String,
 ) -> Result<OAuthFlowResult, String> {
-    let inner = state.inner.lock().await;
+    let mut inner = state.inner.lock().await;
 
     if !inner.status.running {
         return Err("Proxy is not running".to_string());
@@ -687,18 +888,8 @@ pub async fn start_oauth_flow(
         _ => return Err(format!("OAuth not supported for provider: {}", provider)),
     };
 
-    let url = format!("{}{}", inner.management_url(), endpoint);
-    let client = reqwest::Client::new();
-
-    let response: crate::models::OAuthUrlResponse = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", inner.management_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .json()
-        .await
-        .map_err(|e| e.to_string())?;
+    let client = ManagementApiClient::new(&inner);
+    let response = client.oauth_url(endpoint).await.map_err(|e| e.to_string())?;
 
     if let Some(oauth_url) = response.url {
         // Extract or use the state from response
@@ -719,6 +910,15 @@ pub async fn start_oauth_flow(
             log::warn!("Failed to open browser: {}", e);
         }
 
+        inner.oauth_flows.insert(
+            oauth_state.clone(),
+            crate::proxy::OAuthFlow {
+                provider,
+                started_at: std::time::Instant::now(),
+                canceled: false,
+            },
+        );
+
         Ok(OAuthFlowResult {
             url: oauth_url,
             state: oauth_state,
@@ -734,33 +934,86 @@ pub async fn start_oauth_flow(
 pub async fn check_oauth_status(
     state: State<'_, ProxyState>,
     oauth_state: String,
-) -> Result<String, String> {
-    let inner = state.inner.lock().await;
+) -> Result<crate::models::OAuthFlowStatus, String> {
+    use crate::models::OAuthFlowStatus;
+
+    let mut inner = state.inner.lock().await;
 
     if !inner.status.running {
         return Err("Proxy is not running".to_string());
     }
 
+    match inner.oauth_flows.get(&oauth_state) {
+        Some(flow) if flow.canceled => {
+            inner.oauth_flows.remove(&oauth_state);
+            return Ok(OAuthFlowStatus::Canceled);
+        }
+        Some(flow) if flow.started_at.elapsed() > crate::proxy::OAUTH_FLOW_TIMEOUT => {
+            inner.oauth_flows.remove(&oauth_state);
+            return Ok(OAuthFlowStatus::TimedOut);
+        }
+        Some(_) => {}
+        None => return Ok(OAuthFlowStatus::Canceled),
+    }
+
     // Use the correct endpoint format: /get-auth-status?state={state}
     // This matches the Swift implementation in ManagementAPIClient.swift
     let url = format!("{}/get-auth-status?state={}", inner.management_url(), oauth_state);
     let client = reqwest::Client::new();
 
-    let response: crate::models::OAuthStatusResponse = client
+    let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", inner.management_key))
         .send()
         .await
         .map_err(|e| e.to_string())?
-        .json()
+        .json::<crate::models::OAuthStatusResponse>()
         .await
         .map_err(|e| e.to_string())?;
 
-    if let Some(error) = response.error {
-        Err(error)
-    } else {
-        Ok(response.status)
+    let status = match response.status.to_lowercase().as_str() {
+        "completed" | "success" => {
+            inner.oauth_flows.remove(&oauth_state);
+            OAuthFlowStatus::Completed
+        }
+        "denied" | "rejected" => {
+            inner.oauth_flows.remove(&oauth_state);
+            OAuthFlowStatus::Denied
+        }
+        "pending" | "waiting" => OAuthFlowStatus::Pending,
+        _ => {
+            inner.oauth_flows.remove(&oauth_state);
+            OAuthFlowStatus::Error {
+                message: response.error.unwrap_or(response.status),
+            }
+        }
+    };
+
+    Ok(status)
+}
+
+/// Cancel an outstanding OAuth flow, both locally and (best-effort) at the
+/// proxy. `check_oauth_status` will report `Canceled` for this state
+/// afterwards until the entry is pruned.
+#[tauri::command]
+pub async fn cancel_oauth_flow(state: State<'_, ProxyState>, oauth_state: String) -> Result<(), String> {
+    let mut inner = state.inner.lock().await;
+
+    if let Some(flow) = inner.oauth_flows.get_mut(&oauth_state) {
+        flow.canceled = true;
     }
+
+    if inner.status.running {
+        let url = format!("{}/cancel-auth?state={}", inner.management_url(), oauth_state);
+        let client = reqwest::Client::new();
+        let _ = client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", inner.management_key))
+            .send()
+            .await;
+    }
+
+    Ok(())
 }
 
 // ============================================================================
@@ -784,82 +1037,21 @@ pub fn open_logs_folder() -> Result<(), String> {
 
 #[tauri::command]
 pub fn copy_to_clipboard(text: String) -> Result<(), String> {
-    // Use clipboard functionality
-    // This is a simplified implementation - Tauri has clipboard plugins
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-        let mut child = Command::new("pbcopy")
-            .stdin(std::process::Stdio::piped())
-            .spawn()
-            .map_err(|e| e.to_string())?;
-
-        if let Some(stdin) = child.stdin.as_mut() {
-            use std::io::Write;
-            stdin.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
-        }
-
-        child.wait().map_err(|e| e.to_string())?;
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        use std::process::Command;
-        use std::os::windows::process::CommandExt;
-        // CREATE_NO_WINDOW flag (0x08000000) prevents cmd window from appearing
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-        let mut child = Command::new("cmd")
-            .args(["/C", "clip"])
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .creation_flags(CREATE_NO_WINDOW)
-            .spawn()
-            .map_err(|e| e.to_string())?;
-
-        if let Some(stdin) = child.stdin.as_mut() {
-            use std::io::Write;
-            stdin.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
-        }
-
-        child.wait().map_err(|e| e.to_string())?;
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command;
-        // Try xclip first, then xsel
-        let result = Command::new("xclip")
-            .args(["-selection", "clipboard"])
-            .stdin(std::process::Stdio::piped())
-            .spawn()
-            .and_then(|mut child| {
-                if let Some(stdin) = child.stdin.as_mut() {
-                    use std::io::Write;
-                    stdin.write_all(text.as_bytes())?;
-                }
-                child.wait()
-            });
-
-        if result.is_err() {
-            // Fallback to xsel
-            let mut child = Command::new("xsel")
-                .args(["--clipboard", "--input"])
-                .stdin(std::process::Stdio::piped())
-                .spawn()
-                .map_err(|e| e.to_string())?;
-
-            if let Some(stdin) = child.stdin.as_mut() {
-                use std::io::Write;
-                stdin.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
-            }
+    crate::clipboard::copy_text(&text).map_err(|e| e.to_string())
+}
 
-            child.wait().map_err(|e| e.to_string())?;
-        }
-    }
+/// Read the current text contents of the system clipboard, e.g. so the
+/// user can paste an OAuth token straight into `create_auth_file` instead
+/// of hand-typing it.
+#[tauri::command]
+pub fn read_from_clipboard() -> Result<String, String> {
+    crate::clipboard::read_text().map_err(|e| e.to_string())
+}
 
-    Ok(())
+/// Copy raw PNG bytes to the clipboard as image data.
+#[tauri::command]
+pub fn copy_image_to_clipboard(png_bytes: Vec<u8>) -> Result<(), String> {
+    crate::clipboard::copy_image_png(&png_bytes).map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -881,39 +1073,53 @@ pub fn get_shell_profile_path(shell: String) -> Result<String, String> {
 #[tauri::command]
 pub fn is_agent_configured(shell: String, agent: String) -> Result<bool, String> {
     let shell_type = parse_shell_type(&shell)?;
-    let agent_type = parse_agent_type(&agent)?;
-    Ok(shell_profile::is_configured_in_profile(shell_type, agent_type))
+    let manifest = parse_agent_type(&agent)?;
+    Ok(shell_profile::is_configured(shell_type, &manifest.name))
 }
 
 #[tauri::command]
 pub async fn configure_agent(
+    app: AppHandle,
     proxy_state: State<'_, ProxyState>,
     shell: String,
     agent: String,
     api_key: Option<String>,
 ) -> Result<(), String> {
     let shell_type = parse_shell_type(&shell)?;
-    let agent_type = parse_agent_type(&agent)?;
+    let manifest = parse_agent_type(&agent)?;
 
-    let port = {
+    let status = {
         let inner = proxy_state.inner.lock().await;
-        inner.status.port
+        inner.status.clone()
     };
 
-    shell_profile::add_to_profile(
-        shell_type,
-        agent_type,
-        port,
-        api_key.as_deref(),
-    ).map_err(|e: shell_profile::ShellProfileError| e.to_string())
+    let config = manifest.render_profile_config(shell_type, status.port, api_key.as_deref());
+    shell_profile::add_to_profile(shell_type, &manifest.name, &config)
+        .map_err(|e: shell_profile::ShellProfileError| e.to_string())?;
+
+    if let Err(e) = crate::tray::rebuild_tray_menu(&app, status.running, status.port) {
+        log::warn!("failed to rebuild tray menu: {}", e);
+    }
+    Ok(())
 }
 
 #[tauri::command]
-pub fn unconfigure_agent(shell: String, agent: String) -> Result<(), String> {
+pub async fn unconfigure_agent(
+    app: AppHandle,
+    proxy_state: State<'_, ProxyState>,
+    shell: String,
+    agent: String,
+) -> Result<(), String> {
     let shell_type = parse_shell_type(&shell)?;
-    let agent_type = parse_agent_type(&agent)?;
-    shell_profile::remove_from_profile(shell_type, agent_type)
-        .map_err(|e: shell_profile::ShellProfileError| e.to_string())
+    let manifest = parse_agent_type(&agent)?;
+    shell_profile::remove_from_profile(shell_type, &manifest.name)
+        .map_err(|e: shell_profile::ShellProfileError| e.to_string())?;
+
+    let status = proxy_state.inner.lock().await.status.clone();
+    if let Err(e) = crate::tray::rebuild_tray_menu(&app, status.running, status.port) {
+        log::warn!("failed to rebuild tray menu: {}", e);
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -925,19 +1131,51 @@ pub fn create_shell_backup(shell: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn get_env_command(
+pub async fn get_env_command(
+    proxy_state: State<'_, ProxyState>,
+    agent: String,
+    api_key: Option<String>,
+) -> Result<String, String> {
+    let manifest = parse_agent_type(&agent)?;
+
+    let port = {
+        let inner = proxy_state.inner.lock().await;
+        inner.status.port
+    };
+
+    Ok(manifest.render_env_command(shell_profile::detect_shell(), port, api_key.as_deref()))
+}
+
+/// Resolve the agent's binary, point it at the local proxy, and open it in
+/// a terminal window — the one-click version of "run `find_agent_binary`,
+/// copy the `get_env_command` output, open a terminal, paste".
+#[tauri::command]
+pub async fn launch_agent(
     proxy_state: State<'_, ProxyState>,
     agent: String,
     api_key: Option<String>,
-) -> Result<String, String> {
-    let agent_type = parse_agent_type(&agent)?;
+) -> Result<(), String> {
+    let manifest = parse_agent_type(&agent)?;
+
+    let binary_names = if manifest.binary_names.is_empty() {
+        vec![manifest.id.clone()]
+    } else {
+        manifest.binary_names.clone()
+    };
+    let binary_path = find_agent_binary(binary_names)
+        .ok_or_else(|| format!("Could not find the {} binary on this system", manifest.name))?;
 
     let port = {
         let inner = proxy_state.inner.lock().await;
         inner.status.port
     };
 
-    Ok(shell_profile::get_env_command(agent_type, port, api_key.as_deref()))
+    let mut env_vars = vec![(manifest.env_var.clone(), manifest.base_url(port))];
+    if let (Some(key_var), Some(key)) = (manifest.api_key_env_var.clone(), api_key) {
+        env_vars.push((key_var, key));
+    }
+
+    crate::terminal_launch::launch(&binary_path, &env_vars).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -980,36 +1218,26 @@ pub fn get_available_shells() -> Vec<ShellInfo> {
 
 #[tauri::command]
 pub fn get_available_agents() -> Vec<AgentInfo> {
-    vec![
-        AgentInfo {
-            id: "claude-code".to_string(),
-            name: "Claude Code".to_string(),
-            env_var: "ANTHROPIC_BASE_URL".to_string(),
-            description: "Anthropic's Claude AI coding assistant".to_string(),
-            requires_api_key: true,
-        },
-        AgentInfo {
-            id: "gemini-cli".to_string(),
-            name: "Gemini CLI".to_string(),
-            env_var: "GEMINI_API_BASE".to_string(),
-            description: "Google's Gemini AI assistant".to_string(),
-            requires_api_key: false,
-        },
-        AgentInfo {
-            id: "codex".to_string(),
-            name: "Codex (OpenAI)".to_string(),
-            env_var: "OPENAI_BASE_URL".to_string(),
-            description: "OpenAI's Codex coding assistant".to_string(),
-            requires_api_key: true,
-        },
-        AgentInfo {
-            id: "qwen".to_string(),
-            name: "Qwen".to_string(),
-            env_var: "QWEN_BASE_URL".to_string(),
-            description: "Alibaba's Qwen AI assistant".to_string(),
-            requires_api_key: true,
-        },
-    ]
+    agent_registry::all_manifests().into_iter().map(AgentInfo::from).collect()
+}
+
+/// Install a user-supplied agent manifest (JSON file on disk) into the
+/// registry so it shows up in `get_available_agents` alongside the built-ins.
+#[tauri::command]
+pub fn install_agent_manifest(path: String) -> Result<AgentInfo, String> {
+    agent_registry::install_manifest(&path)
+        .map(AgentInfo::from)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_agent_manifests() -> Vec<AgentInfo> {
+    agent_registry::all_manifests().into_iter().map(AgentInfo::from).collect()
+}
+
+#[tauri::command]
+pub fn remove_agent_manifest(id: String) -> Result<(), String> {
+    agent_registry::remove_manifest(&id).map_err(|e| e.to_string())
 }
 
 // Helper types for shell/agent info
@@ -1028,33 +1256,30 @@ pub struct AgentInfo {
     pub env_var: String,
     pub description: String,
     pub requires_api_key: bool,
+    pub builtin: bool,
+}
+
+impl From<AgentManifest> for AgentInfo {
+    fn from(manifest: AgentManifest) -> Self {
+        AgentInfo {
+            id: manifest.id,
+            name: manifest.name,
+            env_var: manifest.env_var,
+            description: manifest.description,
+            requires_api_key: manifest.requires_api_key,
+            builtin: manifest.builtin,
+        }
+    }
 }
 
 // Helper functions
 fn parse_shell_type(shell: &str) -> Result<ShellType, String> {
-    match shell.to_lowercase().as_str() {
-        "zsh" => Ok(ShellType::Zsh),
-        "bash" => Ok(ShellType::Bash),
-        "fish" => Ok(ShellType::Fish),
-        "powershell" | "pwsh" => Ok(ShellType::Powershell),
-        "cmd" => Ok(ShellType::Cmd),
-        _ => Err(format!("Unknown shell type: {}", shell)),
-    }
+    shell_profile::parse_shell_type(shell)
 }
 
-fn parse_agent_type(agent: &str) -> Result<CLIAgent, String> {
-    match agent.to_lowercase().as_str() {
-        "claude-code" | "claude" | "anthropic" => Ok(CLIAgent::ClaudeCode),
-        "gemini-cli" | "gemini" => Ok(CLIAgent::GeminiCLI),
-        "codex" | "openai" => Ok(CLIAgent::Codex),
-        "qwen" => Ok(CLIAgent::Qwen),
-        "iflow" => Ok(CLIAgent::Iflow),
-        "antigravity" => Ok(CLIAgent::Antigravity),
-        "amp" => Ok(CLIAgent::ClaudeCode), // Amp uses similar config
-        "opencode" => Ok(CLIAgent::Codex), // OpenCode uses similar config
-        "factory-droid" => Ok(CLIAgent::Codex), // Factory Droid uses similar config
-        _ => Err(format!("Unknown agent type: {}", agent)),
-    }
+/// Resolve an agent id/alias against the manifest registry.
+fn parse_agent_type(agent: &str) -> Result<AgentManifest, String> {
+    agent_registry::find(agent).map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -1097,7 +1322,7 @@ pub fn scan_auth_files_direct() -> Vec<AuthFile> {
             // Try to parse the auth file
             if let Ok(content) = fs::read_to_string(&path) {
                 if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&content) {
-                    let auth_file = parse_auth_file_content(&filename, &path, &parsed);
+                    let auth_file = parse_auth_file_content(&filename, &path, &decrypt_envelope(&parsed));
                     auth_files.push(auth_file);
                 }
             }
@@ -1127,7 +1352,7 @@ pub fn scan_auth_files_direct() -> Vec<AuthFile> {
 
                     if let Ok(content) = fs::read_to_string(&path) {
                         if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&content) {
-                            let mut auth_file = parse_auth_file_content(&filename, &path, &parsed);
+                            let mut auth_file = parse_auth_file_content(&filename, &path, &decrypt_envelope(&parsed));
                             // Override provider from subdirectory
                             auth_file.provider = subdir.to_string();
                             auth_files.push(auth_file);
@@ -1141,6 +1366,35 @@ pub fn scan_auth_files_direct() -> Vec<AuthFile> {
     auth_files
 }
 
+/// Auth file fields that may hold sensitive tokens and are subject to
+/// at-rest encryption under the `version: 2` envelope.
+const ENCRYPTED_FIELDS: &[&str] = &["access_token", "refresh_token", "accessToken", "refreshToken", "token"];
+
+/// If `parsed` is a `version: 2` envelope, return a copy with its encrypted
+/// fields decrypted back to plaintext; legacy (`version: 1`/absent) files
+/// are returned unchanged. Fields that fail to decrypt are left as-is so a
+/// corrupt entry doesn't hide the whole file from the scan.
+fn decrypt_envelope(parsed: &serde_json::Value) -> serde_json::Value {
+    if parsed.get("version").and_then(|v| v.as_i64()).unwrap_or(1) < 2 {
+        return parsed.clone();
+    }
+
+    let mut decrypted = parsed.clone();
+    if let Some(obj) = decrypted.as_object_mut() {
+        for field in ENCRYPTED_FIELDS {
+            if let Some(serde_json::Value::String(ciphertext)) = obj.get(*field).cloned() {
+                match crate::auth_crypto::decrypt(&ciphertext) {
+                    Ok(plaintext) => {
+                        obj.insert((*field).to_string(), serde_json::Value::String(plaintext));
+                    }
+                    Err(e) => log::warn!("failed to decrypt auth file field {}: {}", field, e),
+                }
+            }
+        }
+    }
+    decrypted
+}
+
 fn parse_auth_file_content(filename: &str, path: &std::path::Path, parsed: &serde_json::Value) -> AuthFile {
     // Determine provider from filename or content
     let provider = if filename.starts_with("gemini") || filename.contains("gemini") {
@@ -1244,11 +1498,13 @@ pub fn create_auth_file(provider: String, email: String, token: String) -> Resul
     let filename = format!("{}-{}.json", provider, safe_email);
     let file_path = auth_dir.join(&filename);
 
-    // Create auth file content
+    // Create auth file content, encrypted at rest under a version 2 envelope
+    let encrypted_token = crate::auth_crypto::encrypt(&token).map_err(|e| e.to_string())?;
     let content = serde_json::json!({
+        "version": 2,
         "provider": provider,
         "email": email,
-        "access_token": token,
+        "access_token": encrypted_token,
         "created_at": chrono::Utc::now().to_rfc3339(),
     });
 
@@ -1341,6 +1597,104 @@ pub fn toggle_auth_file_direct(file_path: String, disable: bool) -> Result<Strin
     Ok(new_path.display().to_string())
 }
 
+/// Rewrite every plaintext (`version` < 2) auth file under the auth dir and
+/// its known provider subdirectories into the encrypted `version: 2`
+/// envelope, verifying each file's round-trip before replacing the
+/// plaintext original. Returns the paths that were migrated.
+#[tauri::command]
+pub fn migrate_auth_files_to_encrypted() -> Result<Vec<String>, String> {
+    use std::fs;
+
+    let auth_dir = crate::proxy::ProxyStateInner::auth_dir();
+    if !auth_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut dirs_to_scan = vec![auth_dir.clone()];
+    let subdirs = ["gemini-cli", "cursor", "trae", "kiro", "copilot", "github-copilot"];
+    for subdir in subdirs {
+        let subdir_path = auth_dir.join(subdir);
+        if subdir_path.is_dir() {
+            dirs_to_scan.push(subdir_path);
+        }
+    }
+
+    let mut migrated = Vec::new();
+    for dir in dirs_to_scan {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() || path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match migrate_one_auth_file(&path) {
+                Ok(true) => migrated.push(path.display().to_string()),
+                Ok(false) => {} // already encrypted, or nothing to encrypt
+                Err(e) => log::warn!("failed to migrate auth file {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    Ok(migrated)
+}
+
+/// Encrypt one plaintext auth file in place: write the encrypted version to
+/// a temp file, verify it decrypts back to the original values, then rename
+/// over the plaintext original. Returns `Ok(false)` if the file was already
+/// `version: 2` (nothing to do).
+fn migrate_one_auth_file(path: &std::path::Path) -> Result<bool, String> {
+    use std::fs;
+
+    let original = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut parsed: serde_json::Value = serde_json::from_str(&original).map_err(|e| e.to_string())?;
+
+    if parsed.get("version").and_then(|v| v.as_i64()).unwrap_or(1) >= 2 {
+        return Ok(false);
+    }
+
+    let Some(obj) = parsed.as_object_mut() else {
+        return Ok(false);
+    };
+
+    let mut plaintext_values = Vec::new();
+    for field in ENCRYPTED_FIELDS {
+        if let Some(serde_json::Value::String(value)) = obj.get(*field).cloned() {
+            let ciphertext = crate::auth_crypto::encrypt(&value).map_err(|e| e.to_string())?;
+            obj.insert((*field).to_string(), serde_json::Value::String(ciphertext));
+            plaintext_values.push((*field, value));
+        }
+    }
+    obj.insert("version".to_string(), serde_json::json!(2));
+
+    if plaintext_values.is_empty() {
+        // Nothing sensitive in this file; leave the plaintext as-is rather
+        // than churning a file with no tokens to protect.
+        return Ok(false);
+    }
+
+    let encrypted_json = serde_json::to_string_pretty(&parsed).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, &encrypted_json).map_err(|e| e.to_string())?;
+
+    // Verify the round-trip before touching the plaintext original.
+    let verify_content = fs::read_to_string(&tmp_path).map_err(|e| e.to_string())?;
+    let verify_parsed: serde_json::Value = serde_json::from_str(&verify_content).map_err(|e| e.to_string())?;
+    for (field, expected) in &plaintext_values {
+        let ciphertext = verify_parsed
+            .get(*field)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("field {} missing after migration", field))?;
+        let decrypted = crate::auth_crypto::decrypt(ciphertext).map_err(|e| e.to_string())?;
+        if &decrypted != expected {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(format!("round-trip verification failed for field {}", field));
+        }
+    }
+
+    fs::rename(&tmp_path, path).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
 // ============================================================================
 // Advanced Agent Configuration
 // ============================================================================
@@ -1373,23 +1727,37 @@ pub struct BackupFile {
 /// Works on macOS, Linux, and Windows
 #[tauri::command]
 pub fn find_agent_binary(binary_names: Vec<String>) -> Option<String> {
+    find_agent_binary_with_strategy(binary_names).map(|(path, _strategy)| path)
+}
+
+/// Same discovery as [`find_agent_binary`], but also reports which
+/// strategy found the binary (`diagnose_environment` surfaces this so
+/// users can tell "found via which" from "found via a version manager").
+fn find_agent_binary_with_strategy(binary_names: Vec<String>) -> Option<(String, &'static str)> {
     let home = dirs::home_dir().unwrap_or_default();
 
+    // Detect sandboxing (Flatpak/Snap/AppImage) and rebuild a host-relative
+    // PATH/XDG_DATA_HOME before walking anything, so discovery below sees
+    // the real user layout rather than whatever the sandbox rewrote it to.
+    let common_joined = std::env::join_paths(crate::paths::discovery_dirs()).ok().and_then(|s| s.into_string().ok());
+    let host_env = HostEnvironment::detect(&home, common_joined);
+
     for name in &binary_names {
         // Strategy 1: Try 'which' on Unix / 'where' on Windows
         // Note: This may not work in GUI apps due to limited PATH
         if let Some(path) = find_via_which_or_where(name) {
-            return Some(path);
+            return Some((path, "which/where"));
         }
 
-        // Strategy 2: Check common static paths
-        if let Some(path) = find_in_common_paths(&home, name) {
-            return Some(path);
+        // Strategy 2: Check the recovered login-shell PATH merged with the
+        // hardcoded common paths
+        if let Some(path) = find_in_search_dirs(&host_env.search_paths, name) {
+            return Some((path, "common path"));
         }
 
         // Strategy 3: Check version managers (nvm, fnm, volta, asdf, mise)
-        if let Some(path) = find_in_version_managers(&home, name) {
-            return Some(path);
+        if let Some(path) = find_in_version_managers(&home, &host_env.xdg_data_home, name) {
+            return Some((path, "version manager"));
         }
     }
 
@@ -1449,69 +1817,9 @@ fn find_via_which_or_where(name: &str) -> Option<String> {
     None
 }
 
-/// Check common binary installation paths
-fn find_in_common_paths(home: &std::path::Path, name: &str) -> Option<String> {
-    use std::path::PathBuf;
-
-    // Common paths for CLI tools (ordered by priority)
-    let common_paths: Vec<PathBuf> = vec![
-        // macOS Homebrew paths
-        PathBuf::from("/opt/homebrew/bin"),          // Apple Silicon
-        PathBuf::from("/usr/local/bin"),             // Intel Mac / Linux
-
-        // System paths
-        PathBuf::from("/usr/bin"),
-        PathBuf::from("/bin"),
-
-        // User local paths
-        home.join(".local/bin"),
-
-        // Package manager paths
-        home.join(".cargo/bin"),                     // Rust/Cargo
-        home.join(".bun/bin"),                       // Bun
-        home.join(".deno/bin"),                      // Deno
-        home.join(".npm-global/bin"),                // npm global
-        home.join("node_modules/.bin"),              // Local npm
-
-        // Tool-specific paths
-        home.join(".opencode/bin"),
-        home.join(".warp/bin"),
-        home.join(".claude/bin"),
-        home.join(".amp/bin"),
-
-        // Version manager shims (static paths)
-        home.join(".volta/bin"),                     // Volta
-        home.join(".asdf/shims"),                    // asdf
-        home.join(".local/share/mise/shims"),        // mise (modern asdf alternative)
-        home.join(".mise/shims"),                    // mise alternative path
-
-        // pnpm
-        home.join(".pnpm"),
-        home.join("Library/pnpm"),                   // macOS pnpm
-
-        // Yarn
-        home.join(".yarn/bin"),
-
-        // Go
-        home.join("go/bin"),
-        home.join(".go/bin"),
-
-        // Additional common paths
-        PathBuf::from("/opt/local/bin"),             // MacPorts
-        PathBuf::from("/snap/bin"),                  // Snap (Linux)
-
-        // Windows-specific paths
-        #[cfg(windows)]
-        home.join("AppData/Local/Programs"),
-        #[cfg(windows)]
-        home.join("AppData/Roaming/npm"),
-        #[cfg(windows)]
-        PathBuf::from("C:/Program Files/nodejs"),
-        #[cfg(windows)]
-        PathBuf::from("C:/ProgramData/chocolatey/bin"),
-    ];
-
-    for dir in common_paths {
+/// Check an arbitrary list of directories (a merged PATH) for `name`
+fn find_in_search_dirs(dirs: &[std::path::PathBuf], name: &str) -> Option<String> {
+    for dir in dirs {
         let binary_path = if cfg!(windows) {
             // Try with .exe, .cmd, .bat extensions on Windows
             let exe_path = dir.join(format!("{}.exe", name));
@@ -1536,7 +1844,7 @@ fn find_in_common_paths(home: &std::path::Path, name: &str) -> Option<String> {
 }
 
 /// Check version managers that use versioned directories (nvm, fnm)
-fn find_in_version_managers(home: &std::path::Path, name: &str) -> Option<String> {
+fn find_in_version_managers(home: &std::path::Path, xdg_data_home: &std::path::Path, name: &str) -> Option<String> {
     use std::path::PathBuf;
 
     // nvm: ~/.nvm/versions/node/v*/bin/<name>
@@ -1547,15 +1855,11 @@ fn find_in_version_managers(home: &std::path::Path, name: &str) -> Option<String
         }
     }
 
-    // fnm: Uses XDG_DATA_HOME or fallback paths
+    // fnm: Uses the normalized XDG_DATA_HOME or fallback paths
     // Modern: $XDG_DATA_HOME/fnm/node-versions/<version>/installation/bin/<name>
     // Legacy: ~/.fnm/node-versions/<version>/installation/bin/<name>
-    let xdg_data = std::env::var("XDG_DATA_HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| home.join(".local/share"));
-
     let fnm_paths = vec![
-        xdg_data.join("fnm/node-versions"),
+        xdg_data_home.join("fnm/node-versions"),
         home.join(".fnm/node-versions"),
         home.join(".local/share/fnm/node-versions"),
     ];
@@ -1576,6 +1880,36 @@ fn find_in_version_managers(home: &std::path::Path, name: &str) -> Option<String
         }
     }
 
+    // asdf: ~/.asdf/installs/nodejs/*/bin/<name>
+    let asdf_versions = home.join(".asdf/installs/nodejs");
+    if asdf_versions.exists() {
+        if let Some(path) = find_in_versioned_dir(&asdf_versions, "bin", name) {
+            return Some(path);
+        }
+    }
+
+    // Volta: ~/.volta/tools/image/node/*/bin/<name>. The flat ~/.volta/bin
+    // shim directory is already covered by the common-paths strategy.
+    let volta_versions = home.join(".volta/tools/image/node");
+    if volta_versions.exists() {
+        if let Some(path) = find_in_versioned_dir(&volta_versions, "bin", name) {
+            return Some(path);
+        }
+    }
+
+    // mise: $XDG_DATA_HOME/mise/installs/node/*/bin/<name>
+    let mise_paths = vec![
+        xdg_data_home.join("mise/installs/node"),
+        home.join(".local/share/mise/installs/node"),
+    ];
+    for mise_base in mise_paths {
+        if mise_base.exists() {
+            if let Some(path) = find_in_versioned_dir(&mise_base, "bin", name) {
+                return Some(path);
+            }
+        }
+    }
+
     // nvm for Windows: $APPDATA/nvm/v*/
     #[cfg(windows)]
     {
@@ -1676,26 +2010,20 @@ fn find_in_fnm_versions(base_dir: &std::path::Path, name: &str) -> Option<String
     None
 }
 
-/// Simple version comparison (handles v1.2.3 format)
-fn version_compare(a: &str, b: &str) -> std::cmp::Ordering {
-    let parse_version = |s: &str| -> Vec<u32> {
-        s.trim_start_matches('v')
-            .split(|c: char| c == '.' || c == '-')
-            .filter_map(|p| p.parse::<u32>().ok())
-            .collect()
-    };
-
-    let a_parts = parse_version(a);
-    let b_parts = parse_version(b);
+/// Order two version-manager directory names (e.g. `v20.0.0`, `20.0.0-rc1`)
+/// by real semver precedence — stable releases sort above prereleases of
+/// the same core version — rather than the dotted-`u32` comparison this
+/// used to do, which silently dropped prerelease/build suffixes and could
+/// treat `v20.0.0-rc1` as equal to `v20.0.0`. Falls back to a lexical
+/// compare for names that don't parse as semver (e.g. `system`, `lts`).
+pub(crate) fn version_compare(a: &str, b: &str) -> std::cmp::Ordering {
+    let parsed_a = semver::Version::parse(a.trim_start_matches('v'));
+    let parsed_b = semver::Version::parse(b.trim_start_matches('v'));
 
-    for (a_part, b_part) in a_parts.iter().zip(b_parts.iter()) {
-        match a_part.cmp(b_part) {
-            std::cmp::Ordering::Equal => continue,
-            other => return other,
-        }
+    match (parsed_a, parsed_b) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
     }
-
-    a_parts.len().cmp(&b_parts.len())
 }
 
 /// Check if a path is executable
@@ -1736,7 +2064,7 @@ pub async fn configure_agent_advanced(
     storage_option: String,
 ) -> Result<(), String> {
     let shell_type = parse_shell_type(&shell)?;
-    let agent_type = parse_agent_type(&agent)?;
+    let manifest = parse_agent_type(&agent)?;
 
     let port = {
         let inner = proxy_state.inner.lock().await;
@@ -1745,10 +2073,13 @@ pub async fn configure_agent_advanced(
 
     // If setup mode is "default", remove proxy configuration
     if config.setup_mode == "default" {
-        return shell_profile::remove_from_profile(shell_type, agent_type)
+        let _ = crate::shims::remove_shims(&manifest.id);
+        return shell_profile::remove_from_profile(shell_type, &manifest.name)
             .map_err(|e| e.to_string());
     }
 
+    let profile_config = manifest.render_profile_config(shell_type, port, Some(&config.api_key));
+
     // Apply configuration based on storage option
     match storage_option.as_str() {
         "json" => {
@@ -1757,40 +2088,68 @@ pub async fn configure_agent_advanced(
         }
         "shell" => {
             // Only update shell profile
-            shell_profile::add_to_profile(
-                shell_type,
-                agent_type,
-                port,
-                Some(&config.api_key),
-            ).map_err(|e| e.to_string())?;
+            shell_profile::add_to_profile(shell_type, &manifest.name, &profile_config)
+                .map_err(|e| e.to_string())?;
+        }
+        "shim" => {
+            // Write a per-agent shim script instead of touching the profile
+            create_agent_shims(&manifest, port, Some(&config.api_key))?;
         }
         "both" | _ => {
             // Update both JSON and shell profile
             configure_agent_json(&agent, &config)?;
-            shell_profile::add_to_profile(
-                shell_type,
-                agent_type,
-                port,
-                Some(&config.api_key),
-            ).map_err(|e| e.to_string())?;
+            shell_profile::add_to_profile(shell_type, &manifest.name, &profile_config)
+                .map_err(|e| e.to_string())?;
         }
     }
 
     Ok(())
 }
 
+/// Resolve `manifest`'s real binary and write shim scripts that export its
+/// proxy env vars before `exec`ing it.
+fn create_agent_shims(manifest: &AgentManifest, port: u16, api_key: Option<&str>) -> Result<(), String> {
+    let binary_names = if manifest.binary_names.is_empty() {
+        vec![manifest.id.clone()]
+    } else {
+        manifest.binary_names.clone()
+    };
+    let real_binary_path = find_agent_binary(binary_names)
+        .ok_or_else(|| format!("Could not find the {} binary on this system", manifest.name))?;
+
+    let mut env_vars = vec![(manifest.env_var.clone(), manifest.base_url(port))];
+    if let (Some(key_var), Some(key)) = (manifest.api_key_env_var.clone(), api_key) {
+        env_vars.push((key_var, key.to_string()));
+    }
+
+    crate::shims::create_shims(manifest, &real_binary_path, &env_vars)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// List every shim script Zest has created, across all agents.
+#[tauri::command]
+pub fn list_agent_shims() -> Vec<crate::shims::ShimRecord> {
+    crate::shims::list_shims()
+}
+
+/// Remove every shim script created for `agent`.
+#[tauri::command]
+pub fn remove_agent_shims(agent: String) -> Result<(), String> {
+    let manifest = parse_agent_type(&agent)?;
+    crate::shims::remove_shims(&manifest.id).map_err(|e| e.to_string())
+}
+
 fn configure_agent_json(agent: &str, config: &AgentConfiguration) -> Result<(), String> {
     use std::fs;
 
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
-
     match agent {
         "claude-code" => {
             // Claude Code uses ~/.claude/settings.json (matching Swift quotio-master)
-            let config_dir = home.join(".claude");
+            let config_dir = crate::paths::claude_config_dir();
             fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
 
-            let config_path = config_dir.join("settings.json");
+            let config_path = crate::paths::claude_settings_path();
 
             // Read existing config to preserve user settings (permissions, hooks, mcpServers, etc.)
             let mut settings: serde_json::Value = if config_path.exists() {
@@ -1853,6 +2212,10 @@ fn configure_agent_json(agent: &str, config: &AgentConfiguration) -> Result<(),
             let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
             fs::write(&config_path, content).map_err(|e| e.to_string())?;
         }
+        "codex" => configure_codex_toml(config)?,
+        "opencode" => configure_opencode_json(config)?,
+        "amp" => configure_amp_json(config)?,
+        "factory-droid" => configure_factory_droid_json(config)?,
         _ => {
             // Other agents might not have JSON config, just use shell profile
         }
@@ -1861,23 +2224,140 @@ fn configure_agent_json(agent: &str, config: &AgentConfiguration) -> Result<(),
     Ok(())
 }
 
+/// Merge the proxy base URL, auth token, and default model into Codex's
+/// `config.toml`, preserving any other keys/tables the user already has
+/// (the `[model_providers.*]` table pattern mirrors how Codex itself
+/// stores multiple provider configs side by side).
+fn configure_codex_toml(config: &AgentConfiguration) -> Result<(), String> {
+    let config_path = crate::paths::codex_config_path();
+
+    let mut doc: toml::Value = if config_path.exists() {
+        let content = std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
+        content.parse::<toml::Value>().unwrap_or_else(|_| toml::Value::Table(Default::default()))
+    } else {
+        toml::Value::Table(Default::default())
+    };
+
+    let base_url = config.proxy_url.trim_end_matches("/v1").to_string();
+    let model = config.model_slots.get("default").cloned().unwrap_or_else(|| "gpt-5-codex".to_string());
+
+    let root = doc.as_table_mut().ok_or("config.toml root is not a table")?;
+    root.insert("model".to_string(), toml::Value::String(model));
+    root.insert("model_provider".to_string(), toml::Value::String("zest".to_string()));
+
+    let providers = root
+        .entry("model_providers".to_string())
+        .or_insert_with(|| toml::Value::Table(Default::default()));
+    let providers = providers.as_table_mut().ok_or("model_providers is not a table")?;
+
+    let mut zest_provider = toml::map::Map::new();
+    zest_provider.insert("name".to_string(), toml::Value::String("Zest Proxy".to_string()));
+    zest_provider.insert("base_url".to_string(), toml::Value::String(format!("{}/v1", base_url)));
+    zest_provider.insert("api_key".to_string(), toml::Value::String(config.api_key.clone()));
+    zest_provider.insert("wire_api".to_string(), toml::Value::String("chat".to_string()));
+    providers.insert("zest".to_string(), toml::Value::Table(zest_provider));
+
+    let content = toml::to_string_pretty(&doc).map_err(|e| e.to_string())?;
+    write_with_backup(&config_path, &content)
+}
+
+fn configure_opencode_json(config: &AgentConfiguration) -> Result<(), String> {
+    let config_path = crate::paths::opencode_config_path();
+    let mut settings = read_json_or_empty(&config_path)?;
+
+    let model = config.model_slots.get("default").cloned().unwrap_or_else(|| "gpt-5-codex".to_string());
+
+    if let Some(obj) = settings.as_object_mut() {
+        let provider = obj.entry("provider".to_string()).or_insert(serde_json::json!({}));
+        if let Some(provider_obj) = provider.as_object_mut() {
+            provider_obj.insert("zest".to_string(), serde_json::json!({
+                "baseURL": config.proxy_url,
+                "apiKey": config.api_key,
+            }));
+        }
+        obj.insert("model".to_string(), serde_json::json!(format!("zest/{}", model)));
+    }
+
+    write_json_with_backup(&config_path, &settings)
+}
+
+fn configure_amp_json(config: &AgentConfiguration) -> Result<(), String> {
+    let config_path = crate::paths::amp_config_path();
+    let mut settings = read_json_or_empty(&config_path)?;
+
+    let base_url = config.proxy_url.trim_end_matches("/v1").to_string();
+
+    if let Some(obj) = settings.as_object_mut() {
+        let env = obj.entry("env".to_string()).or_insert(serde_json::json!({}));
+        if let Some(env_obj) = env.as_object_mut() {
+            env_obj.insert("ANTHROPIC_BASE_URL".to_string(), serde_json::json!(base_url));
+            env_obj.insert("ANTHROPIC_API_KEY".to_string(), serde_json::json!(config.api_key));
+        }
+    }
+
+    write_json_with_backup(&config_path, &settings)
+}
+
+fn configure_factory_droid_json(config: &AgentConfiguration) -> Result<(), String> {
+    let config_path = crate::paths::factory_droid_config_path();
+    let mut settings = read_json_or_empty(&config_path)?;
+
+    let base_url = config.proxy_url.trim_end_matches("/v1").to_string();
+    let model = config.model_slots.get("default").cloned().unwrap_or_else(|| "gpt-5-codex".to_string());
+
+    if let Some(obj) = settings.as_object_mut() {
+        obj.insert("openaiBaseUrl".to_string(), serde_json::json!(base_url));
+        obj.insert("openaiApiKey".to_string(), serde_json::json!(config.api_key));
+        obj.insert("model".to_string(), serde_json::json!(model));
+    }
+
+    write_json_with_backup(&config_path, &settings)
+}
+
+fn read_json_or_empty(path: &std::path::Path) -> Result<serde_json::Value, String> {
+    if path.exists() {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Ok(serde_json::from_str(&content).unwrap_or(serde_json::json!({})))
+    } else {
+        Ok(serde_json::json!({}))
+    }
+}
+
+fn write_json_with_backup(path: &std::path::Path, value: &serde_json::Value) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    write_with_backup(path, &content)
+}
+
+/// Create the parent directory if needed, take a timestamped backup of any
+/// existing file, then write the new content atomically (temp file +
+/// rename).
+fn write_with_backup(path: &std::path::Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    if path.exists() {
+        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("config");
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let backup_path = path.with_file_name(format!("{}.backup.{}", filename, timestamp));
+        std::fs::copy(path, &backup_path).map_err(|e| e.to_string())?;
+    }
+
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("tmp");
+    let tmp_path = path.with_extension(format!("{}.tmp", ext));
+    std::fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp_path, path).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 #[tauri::command]
 pub fn get_agent_backups(agent: String) -> Vec<BackupFile> {
     use std::fs;
 
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => return vec![],
-    };
-
-    let config_dir = match agent.as_str() {
-        "claude-code" => home.join(".claude"),  // Fixed: was ~/.config/claude
-        "codex" => home.join(".codex"),
-        "amp" => home.join(".config").join("amp"),
-        "opencode" => home.join(".config").join("opencode"),
-        "factory-droid" => home.join(".factory"),
-        _ => return vec![],
-    };
+    let Some(config_dir) = crate::paths::agent_config_dir(&agent) else { return vec![] };
 
     if !config_dir.exists() {
         return vec![];
@@ -1923,16 +2403,7 @@ pub fn restore_agent_backup(agent: String, backup_path: String) -> Result<(), St
         return Err("Backup file not found".to_string());
     }
 
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
-
-    let config_path = match agent.as_str() {
-        "claude-code" => home.join(".claude").join("settings.json"),  // Fixed: was ~/.config/claude/config.json
-        "codex" => home.join(".codex").join("config.toml"),
-        "amp" => home.join(".config").join("amp").join("settings.json"),
-        "opencode" => home.join(".config").join("opencode").join("opencode.json"),
-        "factory-droid" => home.join(".factory").join("config.json"),
-        _ => return Err("Unknown agent".to_string()),
-    };
+    let config_path = crate::paths::agent_config_path(&agent).ok_or("Unknown agent")?;
 
     // Create backup of current config first
     if config_path.exists() {
@@ -1952,3 +2423,186 @@ pub fn restore_agent_backup(agent: String, backup_path: String) -> Result<(), St
 
     Ok(())
 }
+
+// ============================================================================
+// Environment Diagnostics
+// ============================================================================
+
+/// What `diagnose_environment` found for a single agent.
+#[derive(serde::Serialize)]
+pub struct AgentDiagnostic {
+    pub agent_id: String,
+    pub agent_name: String,
+    pub binary_path: Option<String>,
+    pub discovery_strategy: Option<String>,
+    pub failure_reason: Option<String>,
+    pub config_path: Option<String>,
+    pub config_has_zest_block: bool,
+    pub backups: Vec<BackupFile>,
+}
+
+/// One Node version manager Zest knows how to discover binaries through,
+/// and the versions it found installed.
+#[derive(serde::Serialize)]
+pub struct VersionManagerInfo {
+    pub name: String,
+    pub installed_versions: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct EnvironmentDiagnostics {
+    pub sandbox: String,
+    pub agents: Vec<AgentDiagnostic>,
+    pub version_managers: Vec<VersionManagerInfo>,
+}
+
+/// Walk the environment the same way `find_agent_binary` does and report
+/// what was found for every known agent, so users who hit "binary not
+/// found" can see why without a shell — analogous to a CLI `info`/`doctor`
+/// command that prints every tool version it can locate.
+#[tauri::command]
+pub fn diagnose_environment() -> EnvironmentDiagnostics {
+    let home = dirs::home_dir().unwrap_or_default();
+    let common_joined = std::env::join_paths(crate::paths::discovery_dirs()).ok().and_then(|s| s.into_string().ok());
+    let host_env = HostEnvironment::detect(&home, common_joined);
+
+    let sandbox = match host_env.sandbox {
+        crate::environment::SandboxKind::None => "none",
+        crate::environment::SandboxKind::Flatpak => "flatpak",
+        crate::environment::SandboxKind::Snap => "snap",
+        crate::environment::SandboxKind::AppImage => "appimage",
+    }
+    .to_string();
+
+    let agents = agent_registry::all_manifests()
+        .into_iter()
+        .map(|manifest| diagnose_agent(&manifest))
+        .collect();
+
+    let version_managers = detect_version_managers(&home, &host_env.xdg_data_home);
+
+    EnvironmentDiagnostics { sandbox, agents, version_managers }
+}
+
+fn diagnose_agent(manifest: &AgentManifest) -> AgentDiagnostic {
+    let binary_names = if manifest.binary_names.is_empty() {
+        vec![manifest.id.clone()]
+    } else {
+        manifest.binary_names.clone()
+    };
+
+    let (binary_path, discovery_strategy, failure_reason) = match find_agent_binary_with_strategy(binary_names) {
+        Some((path, strategy)) => (Some(path), Some(strategy.to_string()), None),
+        None => (None, None, Some(format!("Could not find the {} binary on this system", manifest.name))),
+    };
+
+    let config_path = crate::paths::agent_config_path(&manifest.id);
+    let config_has_zest_block = config_path
+        .as_ref()
+        .map(|path| config_has_zest_block(path, &manifest.id))
+        .unwrap_or(false);
+
+    AgentDiagnostic {
+        agent_id: manifest.id.clone(),
+        agent_name: manifest.name.clone(),
+        binary_path,
+        discovery_strategy,
+        failure_reason,
+        config_path: config_path.map(|p| p.display().to_string()),
+        config_has_zest_block,
+        backups: get_agent_backups(manifest.id.clone()),
+    }
+}
+
+/// Whether the config at `path` already contains a Zest-managed proxy
+/// block, for the agent-specific shapes `configure_agent_json` writes.
+fn config_has_zest_block(path: &std::path::Path, agent_id: &str) -> bool {
+    let Ok(content) = std::fs::read_to_string(path) else { return false };
+
+    if agent_id == "codex" {
+        return content
+            .parse::<toml::Value>()
+            .ok()
+            .and_then(|doc| doc.as_table()?.get("model_providers")?.as_table()?.get("zest").cloned())
+            .is_some();
+    }
+
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&content) else { return false };
+    match agent_id {
+        "opencode" => value.get("provider").and_then(|p| p.get("zest")).is_some(),
+        "claude-code" | "amp" => value
+            .get("env")
+            .and_then(|env| env.get("ANTHROPIC_BASE_URL"))
+            .is_some(),
+        "factory-droid" => value.get("openaiBaseUrl").is_some(),
+        _ => false,
+    }
+}
+
+/// Enumerate the Node version managers `find_in_version_managers` knows
+/// how to search, and the versions each has installed.
+fn detect_version_managers(home: &std::path::Path, xdg_data_home: &std::path::Path) -> Vec<VersionManagerInfo> {
+    let mut managers = Vec::new();
+
+    let nvm_versions = home.join(".nvm/versions/node");
+    if let Some(versions) = list_version_dirs(&nvm_versions) {
+        managers.push(VersionManagerInfo { name: "nvm".to_string(), installed_versions: versions });
+    }
+
+    let fnm_paths = [
+        xdg_data_home.join("fnm/node-versions"),
+        home.join(".fnm/node-versions"),
+        home.join(".local/share/fnm/node-versions"),
+    ];
+    for fnm_base in &fnm_paths {
+        if let Some(versions) = list_version_dirs(fnm_base) {
+            managers.push(VersionManagerInfo { name: "fnm".to_string(), installed_versions: versions });
+            break;
+        }
+    }
+
+    let n_versions = std::path::PathBuf::from("/usr/local/n/versions/node");
+    if let Some(versions) = list_version_dirs(&n_versions) {
+        managers.push(VersionManagerInfo { name: "n".to_string(), installed_versions: versions });
+    }
+
+    let asdf_versions = home.join(".asdf/installs/nodejs");
+    if let Some(versions) = list_version_dirs(&asdf_versions) {
+        managers.push(VersionManagerInfo { name: "asdf".to_string(), installed_versions: versions });
+    }
+
+    let volta_versions = home.join(".volta/tools/image/node");
+    if let Some(versions) = list_version_dirs(&volta_versions) {
+        managers.push(VersionManagerInfo { name: "volta".to_string(), installed_versions: versions });
+    }
+
+    let mise_paths = [xdg_data_home.join("mise/installs/node"), home.join(".local/share/mise/installs/node")];
+    for mise_base in &mise_paths {
+        if let Some(versions) = list_version_dirs(mise_base) {
+            managers.push(VersionManagerInfo { name: "mise".to_string(), installed_versions: versions });
+            break;
+        }
+    }
+
+    managers
+}
+
+/// List the subdirectory names of a versioned install directory, newest
+/// first. Returns `None` if the directory doesn't exist (manager not
+/// installed) so callers can skip it entirely.
+fn list_version_dirs(base_dir: &std::path::Path) -> Option<Vec<String>> {
+    if !base_dir.exists() {
+        return None;
+    }
+
+    let mut versions: Vec<String> = std::fs::read_dir(base_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter_map(|p| p.file_name().and_then(|n| n.to_str()).map(|s| s.to_string()))
+        .collect();
+
+    versions.sort_by(|a, b| version_compare(b, a));
+    Some(versions)
+}
@@ -0,0 +1,208 @@
+//! Hot-reload `config.yaml` and the auth-files directory.
+//!
+//! Watches the proxy's on-disk `config.yaml` and `auth_dir` with `notify`
+//! (backed by inotify on Linux, and the platform-native equivalent
+//! elsewhere) so edits made outside Zest — by hand, or by the proxy itself
+//! — are picked up without a restart. Debounces rapid successive events
+//! (coalescing within ~200ms), the same way `settings::watch_settings_file`
+//! debounces `settings.json` edits, so a single editor save doesn't trigger
+//! multiple reloads.
+
+use crate::models::{AppConfig, AuthFile, LogEntry, LogLevel};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Clone, serde::Serialize)]
+struct ConfigChangedPayload {
+    before: AppConfig,
+    after: AppConfig,
+    diff: serde_json::Value,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum AuthFileChangeKind {
+    Added,
+    Removed,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct AuthFileChangedPayload {
+    kind: AuthFileChangeKind,
+    name: String,
+    file: Option<AuthFile>,
+}
+
+/// Read and parse `config.yaml` at `path` into an [`AppConfig`], decrypting
+/// any sealed `secret_key`/`api_keys` values (see [`AppConfig::unseal`]).
+pub fn load_config(path: &Path) -> Result<AppConfig, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut config: AppConfig = serde_yaml::from_str(&content).map_err(|e| e.to_string())?;
+    config.unseal();
+    Ok(config)
+}
+
+/// Build a sparse diff of the top-level fields that changed between two
+/// `AppConfig` snapshots, as `{field: {"before": ..., "after": ...}}`.
+fn diff_config(before: &AppConfig, after: &AppConfig) -> serde_json::Value {
+    let before_value = serde_json::to_value(before).unwrap_or_default();
+    let after_value = serde_json::to_value(after).unwrap_or_default();
+    let mut diff = serde_json::Map::new();
+
+    if let (Some(before_obj), Some(after_obj)) = (before_value.as_object(), after_value.as_object()) {
+        for (key, after_field) in after_obj {
+            if before_obj.get(key) != Some(after_field) {
+                diff.insert(
+                    key.clone(),
+                    serde_json::json!({ "before": before_obj.get(key), "after": after_field }),
+                );
+            }
+        }
+    }
+
+    serde_json::Value::Object(diff)
+}
+
+fn emit_log<R: tauri::Runtime>(app: &AppHandle<R>, message: String) {
+    let entry = LogEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        level: LogLevel::Info,
+        message,
+        source: Some("config-watcher".to_string()),
+        status_code: None,
+        model: None,
+        provider: None,
+        duration_ms: None,
+    };
+    let _ = app.emit(
+        "log-line",
+        serde_json::json!({ "stream_id": "config-watcher", "entry": entry }),
+    );
+}
+
+/// Scan `auth_dir` the same way `commands::scan_auth_files_direct` does,
+/// keyed by file name, so added/removed entries can be diffed.
+fn scan_auth_files_by_name() -> HashMap<String, AuthFile> {
+    crate::commands::scan_auth_files_direct()
+        .into_iter()
+        .map(|file| (file.name.clone(), file))
+        .collect()
+}
+
+/// Start watching `config.yaml` and `auth_dir` on a dedicated thread for
+/// the lifetime of the app.
+pub fn watch<R: tauri::Runtime>(app: AppHandle<R>) {
+    std::thread::spawn(move || {
+        let config_path = crate::proxy::ProxyStateInner::config_path();
+        let auth_dir = crate::proxy::ProxyStateInner::auth_dir();
+
+        let (tx, rx) = channel();
+        let mut watcher: RecommendedWatcher = match notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                let _ = tx.send(event);
+            }
+        }) {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("Failed to start config.yaml watcher: {}", e);
+                return;
+            }
+        };
+
+        if let Some(config_dir) = config_path.parent() {
+            let _ = std::fs::create_dir_all(config_dir);
+            if let Err(e) = watcher.watch(config_dir, RecursiveMode::NonRecursive) {
+                log::warn!("Failed to watch config directory {:?}: {}", config_dir, e);
+            }
+        }
+        let _ = std::fs::create_dir_all(&auth_dir);
+        if let Err(e) = watcher.watch(&auth_dir, RecursiveMode::NonRecursive) {
+            log::warn!("Failed to watch auth directory {:?}: {}", auth_dir, e);
+        }
+
+        let mut last_config = load_config(&config_path).ok();
+        let mut known_auth_files = scan_auth_files_by_name();
+        let debounce = std::time::Duration::from_millis(200);
+
+        while let Ok(event) = rx.recv() {
+            // Coalesce a burst of events from a single editor save.
+            std::thread::sleep(debounce);
+            let mut paths = event.paths;
+            while let Ok(more) = rx.try_recv() {
+                paths.extend(more.paths);
+            }
+
+            if paths.iter().any(|p| p == &config_path) {
+                reload_config(&app, &config_path, &mut last_config);
+            }
+
+            if paths.iter().any(|p| p.starts_with(&auth_dir)) {
+                reload_auth_files(&app, &mut known_auth_files);
+            }
+        }
+    });
+}
+
+fn reload_config<R: tauri::Runtime>(app: &AppHandle<R>, config_path: &PathBuf, last_config: &mut Option<AppConfig>) {
+    let Ok(new_config) = load_config(config_path) else {
+        return;
+    };
+
+    let Some(before) = last_config.clone() else {
+        *last_config = Some(new_config);
+        return;
+    };
+
+    let diff = diff_config(&before, &new_config);
+    if diff.as_object().map(|o| o.is_empty()).unwrap_or(true) {
+        return;
+    }
+
+    *last_config = Some(new_config.clone());
+    log::info!("Reloaded config.yaml after external edit");
+    emit_log(app, "Reloaded config.yaml after external edit".to_string());
+    let _ = app.emit(
+        "config-changed",
+        ConfigChangedPayload { before, after: new_config, diff },
+    );
+}
+
+fn reload_auth_files<R: tauri::Runtime>(app: &AppHandle<R>, known: &mut HashMap<String, AuthFile>) {
+    let current = scan_auth_files_by_name();
+
+    for (name, file) in &current {
+        if !known.contains_key(name) {
+            emit_log(app, format!("Auth file added: {}", name));
+            let _ = app.emit(
+                "auth-files-changed",
+                AuthFileChangedPayload {
+                    kind: AuthFileChangeKind::Added,
+                    name: name.clone(),
+                    file: Some(file.clone()),
+                },
+            );
+        }
+    }
+
+    for (name, file) in known.iter() {
+        if !current.contains_key(name) {
+            emit_log(app, format!("Auth file removed: {}", name));
+            let mut file = file.clone();
+            file.unavailable = true;
+            let _ = app.emit(
+                "auth-files-changed",
+                AuthFileChangedPayload {
+                    kind: AuthFileChangeKind::Removed,
+                    name: name.clone(),
+                    file: Some(file),
+                },
+            );
+        }
+    }
+
+    *known = current;
+}
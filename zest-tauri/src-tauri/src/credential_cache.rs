@@ -0,0 +1,78 @@
+//! In-process credential cache with TTL and explicit invalidation.
+//!
+//! Mirrors Cargo's `CacheControl` for credential providers: a backend's
+//! `get` conceptually returns a value plus a cache hint, and repeated reads
+//! within that hint's lifetime (e.g. while `fetch_all_quotas` pulls many
+//! provider keys in a row) hit memory instead of spawning a subprocess per
+//! key.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// How long a fetched credential may be served from this cache before
+/// `get_credential` re-invokes the backend.
+#[derive(Debug, Clone, Copy)]
+pub enum CacheControl {
+    /// Never cache; every read re-fetches from the backend.
+    Never,
+    /// Cache for the remainder of the process's lifetime, until explicitly
+    /// invalidated by a `store`/`delete`.
+    Session,
+    /// Cache until the given instant, then re-fetch.
+    ExpiresAt(Instant),
+}
+
+struct CachedCredential {
+    value: String,
+    control: CacheControl,
+}
+
+impl CachedCredential {
+    fn is_live(&self) -> bool {
+        match self.control {
+            CacheControl::Never => false,
+            CacheControl::Session => true,
+            CacheControl::ExpiresAt(expires_at) => Instant::now() < expires_at,
+        }
+    }
+}
+
+static CACHE: Mutex<Option<HashMap<String, CachedCredential>>> = Mutex::new(None);
+
+fn with_cache<R>(f: impl FnOnce(&mut HashMap<String, CachedCredential>) -> R) -> R {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(HashMap::new))
+}
+
+/// Return the cached value for `key`, if present and not expired. An
+/// expired entry is dropped so it isn't checked again.
+pub fn get(key: &str) -> Option<String> {
+    with_cache(|cache| match cache.get(key) {
+        Some(entry) if entry.is_live() => Some(entry.value.clone()),
+        Some(_) => {
+            cache.remove(key);
+            None
+        }
+        None => None,
+    })
+}
+
+/// Record a freshly-fetched value under `control`'s cache hint. A `Never`
+/// hint is a no-op, so the next read re-fetches unconditionally.
+pub fn put(key: &str, value: String, control: CacheControl) {
+    if matches!(control, CacheControl::Never) {
+        return;
+    }
+    with_cache(|cache| {
+        cache.insert(key.to_string(), CachedCredential { value, control });
+    });
+}
+
+/// Drop any cached value for `key`. Called after `store_credential`/
+/// `delete_credential` so a stale value is never served post-write.
+pub fn invalidate(key: &str) {
+    with_cache(|cache| {
+        cache.remove(key);
+    });
+}
@@ -0,0 +1,133 @@
+//! Pluggable external credential-provider process.
+//!
+//! Modeled on Cargo's RFC 2730 credential-process protocol: instead of
+//! hardwiring an OS keychain, a user can point this at any executable and
+//! Zest will launch it once per operation, write a single-line JSON request
+//! to its stdin, and read a single-line JSON response back from its stdout.
+//! This lets users back provider API keys with Vault, `pass`, a cloud KMS,
+//! or anything else that can speak this tiny protocol, without Zest having
+//! to bundle a client for each of them.
+
+use crate::credentials::CredentialError;
+use serde::{Deserialize, Serialize};
+
+/// An external process this store, fetch or erase request should be sent to.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CredentialProcessConfig {
+    /// Path to the executable, resolved via `PATH` if not absolute.
+    pub command: String,
+    /// Extra arguments passed before the JSON request is written to stdin.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    Get,
+    Store,
+    Erase,
+}
+
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    v: u8,
+    action: Action,
+    service: &'a str,
+    key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+enum Response {
+    Ok(ResponseOk),
+    Err(ResponseErr),
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseOk {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseErr {
+    kind: String,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Run `config.command` with `config.args`, write `request` as a single
+/// line of JSON to its stdin, and parse a single line of JSON back from its
+/// stdout.
+fn invoke(config: &CredentialProcessConfig, request: &Request<'_>) -> Result<Option<String>, CredentialError> {
+    use std::io::Write;
+
+    let line = serde_json::to_string(request)
+        .map_err(|e| CredentialError::StoreError(format!("failed to encode credential-process request: {e}")))?;
+
+    let mut child = std::process::Command::new(&config.command)
+        .args(&config.args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| CredentialError::StoreError(format!("failed to launch credential process '{}': {e}", config.command)))?;
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        writeln!(stdin, "{line}")
+            .map_err(|e| CredentialError::StoreError(format!("failed to write to credential process stdin: {e}")))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| CredentialError::StoreError(format!("credential process '{}' failed: {e}", config.command)))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().next().unwrap_or("").trim();
+    if first_line.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(CredentialError::RetrieveError(format!(
+            "credential process '{}' produced no output{}",
+            config.command,
+            if stderr.is_empty() { String::new() } else { format!(": {stderr}") }
+        )));
+    }
+
+    let response: Response = serde_json::from_str(first_line)
+        .map_err(|e| CredentialError::RetrieveError(format!("invalid credential process response: {e}")))?;
+
+    match response {
+        Response::Ok(ok) => Ok(ok.token),
+        Response::Err(err) => Err(map_error_kind(&err)),
+    }
+}
+
+fn map_error_kind(err: &ResponseErr) -> CredentialError {
+    match err.kind.as_str() {
+        "not-found" => CredentialError::NotFound,
+        _ => CredentialError::RetrieveError(
+            err.message.clone().unwrap_or_else(|| err.kind.clone()),
+        ),
+    }
+}
+
+pub fn get(config: &CredentialProcessConfig, service: &str, key: &str) -> Result<String, CredentialError> {
+    let request = Request { v: 1, action: Action::Get, service, key, value: None };
+    invoke(config, &request)?.ok_or(CredentialError::NotFound)
+}
+
+pub fn store(config: &CredentialProcessConfig, service: &str, key: &str, value: &str) -> Result<(), CredentialError> {
+    let request = Request { v: 1, action: Action::Store, service, key, value: Some(value) };
+    invoke(config, &request).map(|_| ())
+}
+
+pub fn erase(config: &CredentialProcessConfig, service: &str, key: &str) -> Result<(), CredentialError> {
+    let request = Request { v: 1, action: Action::Erase, service, key, value: None };
+    match invoke(config, &request) {
+        Ok(_) => Ok(()),
+        Err(CredentialError::NotFound) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
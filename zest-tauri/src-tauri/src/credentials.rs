@@ -5,10 +5,12 @@
 //! On macOS: Keychain
 //! On Linux: Secret Service (libsecret)
 
+use crate::credential_process::CredentialProcessConfig;
+use crate::onepassword::OnePasswordConfig;
 use serde::Serialize;
 use thiserror::Error;
 
-const SERVICE_NAME: &str = "com.zest.app";
+pub(crate) const SERVICE_NAME: &str = "com.zest.app";
 
 #[derive(Error, Debug, Serialize)]
 pub enum CredentialError {
@@ -20,130 +22,221 @@ pub enum CredentialError {
     DeleteError(String),
     #[error("Credential not found")]
     NotFound,
+    #[error("not signed in to the 1Password CLI; run `op signin` and retry")]
+    NotSignedIn,
 }
 
-/// Store a credential securely
+/// Which store backs `store_credential`/`get_credential`/`delete_credential`.
+/// Defaults to the OS-native keychain; `Process` supersedes it with an
+/// arbitrary external credential-process executable (see
+/// [`crate::credential_process`]), the same way Cargo's credential-process
+/// setting supersedes its own default credential store.
+#[derive(Debug, Clone, Default)]
+pub enum CredentialBackend {
+    #[default]
+    Os,
+    Process(CredentialProcessConfig),
+    OnePassword(OnePasswordConfig),
+}
+
+/// Store a credential securely, using the OS-native keychain.
 pub fn store_credential(key: &str, value: &str) -> Result<(), CredentialError> {
-    #[cfg(target_os = "windows")]
-    {
-        store_credential_windows(key, value)
-    }
+    store_credential_with_backend(key, value, &CredentialBackend::Os)
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        store_credential_macos(key, value)
-    }
+/// Retrieve a credential from the OS-native keychain.
+pub fn get_credential(key: &str) -> Result<String, CredentialError> {
+    get_credential_with_backend(key, &CredentialBackend::Os)
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        store_credential_linux(key, value)
-    }
+/// Delete a credential from the OS-native keychain.
+pub fn delete_credential(key: &str) -> Result<(), CredentialError> {
+    delete_credential_with_backend(key, &CredentialBackend::Os)
 }
 
-/// Retrieve a credential
-pub fn get_credential(key: &str) -> Result<String, CredentialError> {
-    #[cfg(target_os = "windows")]
-    {
-        get_credential_windows(key)
-    }
+/// Store a credential using `backend`, invalidating any cached read so a
+/// stale value is never served afterwards.
+pub fn store_credential_with_backend(key: &str, value: &str, backend: &CredentialBackend) -> Result<(), CredentialError> {
+    let result = match backend {
+        CredentialBackend::Process(config) => crate::credential_process::store(config, SERVICE_NAME, key, value),
+        CredentialBackend::OnePassword(config) => crate::onepassword::store(config, key, value),
+        CredentialBackend::Os => {
+            #[cfg(target_os = "windows")]
+            {
+                store_credential_windows(key, value)
+            }
 
-    #[cfg(target_os = "macos")]
-    {
-        get_credential_macos(key)
-    }
+            #[cfg(target_os = "macos")]
+            {
+                store_credential_macos(key, value)
+            }
 
-    #[cfg(target_os = "linux")]
-    {
-        get_credential_linux(key)
-    }
+            #[cfg(target_os = "linux")]
+            {
+                store_credential_linux(key, value)
+            }
+        }
+    };
+    crate::credential_cache::invalidate(key);
+    result
 }
 
-/// Delete a credential
-pub fn delete_credential(key: &str) -> Result<(), CredentialError> {
-    #[cfg(target_os = "windows")]
-    {
-        delete_credential_windows(key)
+/// Retrieve a credential using `backend`, serving from the in-process cache
+/// when a live entry exists.
+pub fn get_credential_with_backend(key: &str, backend: &CredentialBackend) -> Result<String, CredentialError> {
+    if let Some(cached) = crate::credential_cache::get(key) {
+        return Ok(cached);
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        delete_credential_macos(key)
-    }
+    let (value, control) = get_credential_from_backend(key, backend)?;
+    crate::credential_cache::put(key, value.clone(), control);
+    Ok(value)
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        delete_credential_linux(key)
-    }
+fn get_credential_from_backend(
+    key: &str,
+    backend: &CredentialBackend,
+) -> Result<(String, crate::credential_cache::CacheControl), CredentialError> {
+    use crate::credential_cache::CacheControl;
+
+    let value = match backend {
+        CredentialBackend::Process(config) => crate::credential_process::get(config, SERVICE_NAME, key)?,
+        CredentialBackend::OnePassword(config) => crate::onepassword::get(config, key)?,
+        CredentialBackend::Os => {
+            #[cfg(target_os = "windows")]
+            {
+                get_credential_windows(key)?
+            }
+
+            #[cfg(target_os = "macos")]
+            {
+                get_credential_macos(key)?
+            }
+
+            #[cfg(target_os = "linux")]
+            {
+                get_credential_linux(key)?
+            }
+        }
+    };
+
+    // None of today's backends carry their own TTL, so a successful fetch
+    // is cached for the rest of the process's lifetime; `store`/`delete`
+    // invalidate it explicitly rather than relying on expiry.
+    Ok((value, CacheControl::Session))
+}
+
+/// Delete a credential using `backend`, invalidating any cached read.
+pub fn delete_credential_with_backend(key: &str, backend: &CredentialBackend) -> Result<(), CredentialError> {
+    let result = match backend {
+        CredentialBackend::Process(config) => crate::credential_process::erase(config, SERVICE_NAME, key),
+        CredentialBackend::OnePassword(config) => crate::onepassword::erase(config, key),
+        CredentialBackend::Os => {
+            #[cfg(target_os = "windows")]
+            {
+                delete_credential_windows(key)
+            }
+
+            #[cfg(target_os = "macos")]
+            {
+                delete_credential_macos(key)
+            }
+
+            #[cfg(target_os = "linux")]
+            {
+                delete_credential_linux(key)
+            }
+        }
+    };
+    crate::credential_cache::invalidate(key);
+    result
 }
 
 // ============================================================================
 // Windows Implementation
 // ============================================================================
 
+// Windows Credential Manager via `Win32::Security::Credentials`, storing
+// and reading back the same `CRED_TYPE_GENERIC` target so store/get/delete
+// are actually consistent with each other (the previous `cmdkey`-writes /
+// `.enc`-file-reads split meant nothing ever round-tripped). No `cmd`
+// windows are spawned and no file/registry path is used.
+
+#[cfg(target_os = "windows")]
+fn target_name(key: &str) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    std::ffi::OsString::from(format!("{}:{}", SERVICE_NAME, key))
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
 #[cfg(target_os = "windows")]
 fn store_credential_windows(key: &str, value: &str) -> Result<(), CredentialError> {
-    use windows_registry::*;
-    use std::os::windows::process::CommandExt;
-    // CREATE_NO_WINDOW flag (0x08000000) prevents cmd window from appearing
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-    // For simplicity, we'll store in a protected registry location
-    // In production, you might want to use the Windows Credential Manager API directly
-    let target_name = format!("{}:{}", SERVICE_NAME, key);
-
-    // Use the Windows Credential Manager via command line
-    // This is a simplified approach - a production app should use the Windows API
-    let result = std::process::Command::new("cmd")
-        .args(["/C", &format!(
-            "cmdkey /generic:{} /user:zest /pass:{}",
-            target_name, value
-        )])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output();
-
-    match result {
-        Ok(output) if output.status.success() => Ok(()),
-        Ok(output) => Err(CredentialError::StoreError(
-            String::from_utf8_lossy(&output.stderr).to_string()
-        )),
-        Err(e) => Err(CredentialError::StoreError(e.to_string())),
-    }
+    use windows::core::PWSTR;
+    use windows::Win32::Security::Credentials::{CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC};
+
+    let mut target = target_name(key);
+    let blob = value.as_bytes().to_vec();
+    let mut user_name: Vec<u16> = std::ffi::OsString::from("zest")
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let credential = CREDENTIALW {
+        Flags: Default::default(),
+        Type: CRED_TYPE_GENERIC,
+        TargetName: PWSTR(target.as_mut_ptr()),
+        Comment: PWSTR::null(),
+        LastWritten: Default::default(),
+        CredentialBlobSize: blob.len() as u32,
+        CredentialBlob: blob.as_ptr() as *mut u8,
+        Persist: CRED_PERSIST_LOCAL_MACHINE,
+        AttributeCount: 0,
+        Attributes: std::ptr::null_mut(),
+        TargetAlias: PWSTR::null(),
+        UserName: PWSTR(user_name.as_mut_ptr()),
+    };
+
+    unsafe { CredWriteW(&credential, 0) }.map_err(|e| CredentialError::StoreError(e.to_string()))
 }
 
 #[cfg(target_os = "windows")]
 fn get_credential_windows(key: &str) -> Result<String, CredentialError> {
-    // For Windows, we'll fall back to file-based storage with encryption
-    // A full implementation would use the Credential Manager API
-    let path = dirs::data_local_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("Zest")
-        .join("credentials")
-        .join(format!("{}.enc", key));
-
-    if !path.exists() {
-        return Err(CredentialError::NotFound);
-    }
+    use windows::Win32::Foundation::ERROR_NOT_FOUND;
+    use windows::Win32::Security::Credentials::{CredFree, CredReadW, CRED_TYPE_GENERIC};
+
+    let target = target_name(key);
+    let mut credential = std::ptr::null_mut();
+
+    unsafe { CredReadW(windows::core::PCWSTR(target.as_ptr()), CRED_TYPE_GENERIC.0, 0, &mut credential) }.map_err(|e| {
+        if e.code() == windows::core::HRESULT::from_win32(ERROR_NOT_FOUND.0) {
+            CredentialError::NotFound
+        } else {
+            CredentialError::RetrieveError(e.to_string())
+        }
+    })?;
 
-    std::fs::read_to_string(&path)
-        .map_err(|e| CredentialError::RetrieveError(e.to_string()))
+    let result = unsafe {
+        let cred = &*credential;
+        let blob = std::slice::from_raw_parts(cred.CredentialBlob, cred.CredentialBlobSize as usize);
+        String::from_utf8(blob.to_vec()).map_err(|e| CredentialError::RetrieveError(e.to_string()))
+    };
+
+    unsafe { let _ = CredFree(credential as *const _); }
+
+    result
 }
 
 #[cfg(target_os = "windows")]
 fn delete_credential_windows(key: &str) -> Result<(), CredentialError> {
-    use std::os::windows::process::CommandExt;
-    // CREATE_NO_WINDOW flag (0x08000000) prevents cmd window from appearing
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-    let target_name = format!("{}:{}", SERVICE_NAME, key);
+    use windows::Win32::Foundation::ERROR_NOT_FOUND;
+    use windows::Win32::Security::Credentials::{CredDeleteW, CRED_TYPE_GENERIC};
 
-    let result = std::process::Command::new("cmd")
-        .args(["/C", &format!("cmdkey /delete:{}", target_name)])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output();
-
-    match result {
-        Ok(output) if output.status.success() => Ok(()),
-        Ok(_) => Ok(()), // Ignore errors when deleting non-existent credentials
+    let target = target_name(key);
+    match unsafe { CredDeleteW(windows::core::PCWSTR(target.as_ptr()), CRED_TYPE_GENERIC.0, 0) } {
+        Ok(()) => Ok(()),
+        Err(e) if e.code() == windows::core::HRESULT::from_win32(ERROR_NOT_FOUND.0) => Ok(()),
         Err(e) => Err(CredentialError::DeleteError(e.to_string())),
     }
 }
@@ -216,97 +309,108 @@ fn delete_credential_macos(key: &str) -> Result<(), CredentialError> {
 // Linux Implementation
 // ============================================================================
 
-#[cfg(target_os = "linux")]
-fn store_credential_linux(key: &str, value: &str) -> Result<(), CredentialError> {
-    // Use secret-tool if available (part of libsecret)
-    let output = std::process::Command::new("secret-tool")
-        .args([
-            "store",
-            "--label", &format!("Zest: {}", key),
-            "application", SERVICE_NAME,
-            "key", key,
-        ])
-        .stdin(std::process::Stdio::piped())
-        .spawn()
-        .and_then(|mut child| {
-            use std::io::Write;
-            if let Some(stdin) = child.stdin.as_mut() {
-                stdin.write_all(value.as_bytes())?;
-            }
-            child.wait_with_output()
-        })
-        .map_err(|e| CredentialError::StoreError(e.to_string()))?;
+// oo7 talks to `org.freedesktop.Secret.Service` over D-Bus directly, and
+// transparently swaps in the `org.freedesktop.portal.Secret` portal when the
+// app is running sandboxed (Flatpak/Snap), where the raw Secret Service bus
+// name usually isn't reachable. There's no plaintext-file fallback: a
+// Secret Service failure surfaces as a `StoreError`/`RetrieveError` rather
+// than silently writing an unencrypted credential to disk.
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        // Fall back to file-based storage
-        let path = dirs::data_local_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("zest")
-            .join("credentials");
+#[cfg(target_os = "linux")]
+fn item_attributes(key: &str) -> std::collections::HashMap<&str, &str> {
+    std::collections::HashMap::from([("application", SERVICE_NAME), ("key", key)])
+}
 
-        std::fs::create_dir_all(&path)
-            .map_err(|e| CredentialError::StoreError(e.to_string()))?;
+/// A current-thread Tokio runtime dedicated to bridging `oo7`'s async D-Bus
+/// calls into this module's synchronous API. Only ever `block_on`'d from
+/// the fresh OS thread `store/get/delete_credential_linux` spawn below,
+/// never from the caller's own thread: these functions are reached from
+/// `async fn` Tauri commands already running on the global Tokio runtime,
+/// and calling `block_on` there (on any runtime, this one included) panics
+/// with "Cannot start a runtime from within a runtime".
+#[cfg(target_os = "linux")]
+fn oo7_runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build dedicated oo7 runtime")
+    })
+}
 
-        std::fs::write(path.join(key), value)
-            .map_err(|e| CredentialError::StoreError(e.to_string()))
-    }
+#[cfg(target_os = "linux")]
+fn store_credential_linux(key: &str, value: &str) -> Result<(), CredentialError> {
+    let key = key.to_string();
+    let value = value.to_string();
+    std::thread::spawn(move || {
+        oo7_runtime().block_on(async move {
+            let keyring = oo7::Keyring::new()
+                .await
+                .map_err(|e| CredentialError::StoreError(e.to_string()))?;
+
+            keyring
+                .create_item(&format!("Zest: {key}"), &item_attributes(&key), value.as_bytes(), true)
+                .await
+                .map_err(|e| CredentialError::StoreError(e.to_string()))
+        })
+    })
+    .join()
+    .unwrap_or_else(|_| Err(CredentialError::StoreError("oo7 keyring worker thread panicked".to_string())))
 }
 
 #[cfg(target_os = "linux")]
 fn get_credential_linux(key: &str) -> Result<String, CredentialError> {
-    // Try secret-tool first
-    let output = std::process::Command::new("secret-tool")
-        .args([
-            "lookup",
-            "application", SERVICE_NAME,
-            "key", key,
-        ])
-        .output()
-        .map_err(|e| CredentialError::RetrieveError(e.to_string()))?;
-
-    if output.status.success() && !output.stdout.is_empty() {
-        return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
-    }
-
-    // Fall back to file-based storage
-    let path = dirs::data_local_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("zest")
-        .join("credentials")
-        .join(key);
-
-    if path.exists() {
-        std::fs::read_to_string(&path)
-            .map_err(|e| CredentialError::RetrieveError(e.to_string()))
-    } else {
-        Err(CredentialError::NotFound)
-    }
+    let key = key.to_string();
+    std::thread::spawn(move || {
+        oo7_runtime().block_on(async move {
+            let keyring = oo7::Keyring::new()
+                .await
+                .map_err(|e| CredentialError::RetrieveError(e.to_string()))?;
+
+            let items = keyring
+                .search_items(&item_attributes(&key))
+                .await
+                .map_err(|e| CredentialError::RetrieveError(e.to_string()))?;
+
+            let item = items.first().ok_or(CredentialError::NotFound)?;
+            let secret = item
+                .secret()
+                .await
+                .map_err(|e| CredentialError::RetrieveError(e.to_string()))?;
+
+            String::from_utf8(secret.to_vec()).map_err(|e| CredentialError::RetrieveError(e.to_string()))
+        })
+    })
+    .join()
+    .unwrap_or_else(|_| Err(CredentialError::RetrieveError("oo7 keyring worker thread panicked".to_string())))
 }
 
 #[cfg(target_os = "linux")]
 fn delete_credential_linux(key: &str) -> Result<(), CredentialError> {
-    // Try secret-tool first
-    let _ = std::process::Command::new("secret-tool")
-        .args([
-            "clear",
-            "application", SERVICE_NAME,
-            "key", key,
-        ])
-        .output();
-
-    // Also try to delete file-based credential
-    let path = dirs::data_local_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("zest")
-        .join("credentials")
-        .join(key);
-
-    if path.exists() {
-        std::fs::remove_file(&path)
-            .map_err(|e| CredentialError::DeleteError(e.to_string()))?;
-    }
+    let key = key.to_string();
+    std::thread::spawn(move || {
+        oo7_runtime().block_on(async move {
+            let keyring = oo7::Keyring::new()
+                .await
+                .map_err(|e| CredentialError::DeleteError(e.to_string()))?;
+
+            let items = keyring
+                .search_items(&item_attributes(&key))
+                .await
+                .map_err(|e| CredentialError::DeleteError(e.to_string()))?;
+
+            if items.is_empty() {
+                return Ok(());
+            }
 
-    Ok(())
+            for item in items {
+                item.delete().await.map_err(|e| CredentialError::DeleteError(e.to_string()))?;
+            }
+
+            Ok(())
+        })
+    })
+    .join()
+    .unwrap_or_else(|_| Err(CredentialError::DeleteError("oo7 keyring worker thread panicked".to_string())))
 }
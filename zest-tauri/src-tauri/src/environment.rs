@@ -0,0 +1,152 @@
+//! Environment normalization for agent binary discovery.
+//!
+//! GUI-launched processes inherit a minimal environment, and when Zest
+//! itself is packaged as a Flatpak, Snap, or AppImage, `PATH` and
+//! `XDG_DATA_HOME` are rewritten to point inside the sandbox rather than
+//! at the user's real home directory — so nvm/fnm/n installs there are
+//! never found. This runs before `find_agent_binary` walks anything: it
+//! detects the sandbox, recovers the login-shell `PATH`/`XDG_DATA_DIRS`
+//! (relaying through `flatpak-spawn --host` when needed), and rebuilds a
+//! canonical [`HostEnvironment`] that the rest of the discovery code
+//! resolves binaries against instead of the raw process environment.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Which desktop packaging sandbox (if any) Zest is currently running
+/// inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxKind {
+    None,
+    Flatpak,
+    Snap,
+    AppImage,
+}
+
+impl SandboxKind {
+    fn detect() -> Self {
+        if Path::new("/.flatpak-info").exists() || std::env::var("container").map(|v| v == "flatpak").unwrap_or(false) {
+            SandboxKind::Flatpak
+        } else if std::env::var("SNAP").is_ok() || std::env::var("SNAP_NAME").is_ok() {
+            SandboxKind::Snap
+        } else if std::env::var("APPIMAGE").is_ok() || std::env::var("APPDIR").is_ok() {
+            SandboxKind::AppImage
+        } else {
+            SandboxKind::None
+        }
+    }
+
+    pub fn is_sandboxed(&self) -> bool {
+        !matches!(self, SandboxKind::None)
+    }
+}
+
+/// The canonical, host-relative environment that agent binary discovery
+/// should resolve against, rebuilt from whatever `PATH`/`XDG_*` the
+/// process actually inherited.
+#[derive(Debug, Clone)]
+pub struct HostEnvironment {
+    pub sandbox: SandboxKind,
+    pub search_paths: Vec<PathBuf>,
+    pub xdg_data_home: PathBuf,
+}
+
+impl HostEnvironment {
+    /// Detect the sandbox and rebuild the search environment against
+    /// `home`, merging the recovered login-shell `PATH` with the
+    /// hardcoded common paths.
+    pub fn detect(home: &Path, common_paths_joined: Option<String>) -> Self {
+        let sandbox = SandboxKind::detect();
+
+        let recovered_path = recover_env_var_via_login_shell("PATH");
+        let search_paths = normalize_pathlist(&[recovered_path, std::env::var("PATH").ok(), common_paths_joined]);
+
+        // Merge XDG_DATA_DIRS so later .desktop/version-manager lookups in
+        // this process see the same host-relative value the recovered PATH
+        // is based on.
+        let recovered_xdg_dirs = recover_env_var_via_login_shell("XDG_DATA_DIRS");
+        let merged_xdg_dirs = normalize_pathlist(&[recovered_xdg_dirs, std::env::var("XDG_DATA_DIRS").ok()]);
+        if let Some(joined) = std::env::join_paths(&merged_xdg_dirs).ok().and_then(|s| s.into_string().ok()) {
+            std::env::set_var("XDG_DATA_DIRS", joined);
+        }
+
+        // Inside a sandbox, XDG_DATA_HOME is rewritten to a container-local
+        // path (e.g. ~/.var/app/<id>/data), so reconstruct it against the
+        // real home rather than trusting the inherited value.
+        let xdg_data_home = if sandbox.is_sandboxed() {
+            home.join(".local/share")
+        } else {
+            std::env::var("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| home.join(".local/share"))
+        };
+
+        HostEnvironment { sandbox, search_paths, xdg_data_home }
+    }
+}
+
+/// Merge several `PATH`-style lists (each itself separator-joined) into a
+/// single deduplicated, order-preserving list — first occurrence wins, so
+/// the most authoritative source should be passed first.
+pub fn normalize_pathlist(lists: &[Option<String>]) -> Vec<PathBuf> {
+    let separator = if cfg!(windows) { ';' } else { ':' };
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+
+    for list in lists.iter().flatten() {
+        for entry in list.split(separator) {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            if seen.insert(entry.to_string()) {
+                result.push(PathBuf::from(entry));
+            }
+        }
+    }
+
+    result
+}
+
+/// Recover `var` as seen by the user's real login shell, since GUI-launched
+/// processes typically inherit a minimal PATH that omits whatever a
+/// version manager (nvm, fnm, homebrew, etc.) appended in `.zshrc`/`.bashrc`.
+/// Under Flatpak, the lookup is relayed to the host via `flatpak-spawn
+/// --host` so the result reflects paths the user can actually invoke
+/// outside the sandbox rather than the container's isolated `/app/bin`;
+/// under Snap or AppImage the shell is run directly.
+///
+/// Windows has no equivalent login-shell-appends-to-PATH step (GUI
+/// processes inherit the full user/system PATH straight from the
+/// registry), so this is a no-op there.
+#[cfg(unix)]
+fn recover_env_var_via_login_shell(var: &str) -> Option<String> {
+    use std::process::{Command, Stdio};
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    let script = format!("printf %s \"${}\"", var);
+
+    let mut command = if SandboxKind::detect() == SandboxKind::Flatpak && Path::new("/.flatpak-info").exists() {
+        let mut c = Command::new("flatpak-spawn");
+        c.args(["--host", &shell, "-ilc", &script]);
+        c
+    } else {
+        let mut c = Command::new(&shell);
+        c.args(["-ilc", &script]);
+        c
+    };
+
+    let output = command.stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if value.is_empty() { None } else { Some(value) }
+}
+
+#[cfg(windows)]
+fn recover_env_var_via_login_shell(_var: &str) -> Option<String> {
+    None
+}
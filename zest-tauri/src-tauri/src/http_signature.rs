@@ -0,0 +1,185 @@
+//! HTTP Message Signatures for the remote management API.
+//!
+//! When `RemoteManagementConfig::allow_remote` is enabled, a request is
+//! otherwise only guarded by the shared `secret_key`. This adds a second,
+//! integrity-checked layer: every request carries a `Digest` header over its
+//! body and a `Signature` header covering `(request-target) host date
+//! digest`, signed with an Ed25519 key (via the `sigh` crate) so a verifier
+//! can confirm both the sender's identity and that nothing in transit was
+//! altered. `http_digest_headers` computes the `Digest` header itself so the
+//! digest algorithm name/format stays consistent with what other HTTP
+//! signature implementations expect.
+
+use base64::Engine;
+use http_digest_headers::{DigestHeader, Sha256Digest};
+use sigh::{Algorithm, Key, PrivateKey, PublicKey, SigningConfig};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Headers covered by the signing string, in order. Both signer and
+/// verifier must agree on this list.
+const SIGNED_HEADERS: &str = "(request-target) host date digest";
+
+/// Reject a `Date` header further than this from the current time, to block
+/// replay of a captured request.
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+#[derive(Error, Debug)]
+pub enum SignatureError {
+    #[error("failed to read key file {0}: {1}")]
+    KeyFile(String, String),
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+    #[error("signing failed: {0}")]
+    SigningFailed(String),
+    #[error("missing Signature header")]
+    MissingSignature,
+    #[error("malformed Signature header")]
+    MalformedSignature,
+    #[error("signature verification failed")]
+    VerificationFailed,
+    #[error("Date header is missing or out of the allowed {0:?} skew")]
+    StaleDate(Duration),
+}
+
+/// An Ed25519 key pair loaded from the paths in `RemoteManagementConfig::oauth_signature_keys`.
+pub struct SignatureKeyPair {
+    pub key_id: String,
+    private_key: PrivateKey,
+    public_key: PublicKey,
+}
+
+impl SignatureKeyPair {
+    /// Load a PEM-encoded Ed25519 private key from `private_key_path` and
+    /// derive its public key, identified to verifiers as `key_id` (the
+    /// `RemoteManagementConfig`'s configured id, typically a hostname or
+    /// instance name).
+    pub fn load(key_id: String, private_key_path: &Path) -> Result<Self, SignatureError> {
+        let pem = std::fs::read_to_string(private_key_path)
+            .map_err(|e| SignatureError::KeyFile(private_key_path.display().to_string(), e.to_string()))?;
+        let private_key = PrivateKey::from_pem(&pem).map_err(|e| SignatureError::InvalidKey(e.to_string()))?;
+        let public_key = private_key.public_key().map_err(|e| SignatureError::InvalidKey(e.to_string()))?;
+        Ok(Self { key_id, private_key, public_key })
+    }
+}
+
+/// Build the `Digest: SHA-256=<base64>` header value for `body`.
+pub fn digest_header(body: &[u8]) -> String {
+    Sha256Digest::digest(body).to_header_value()
+}
+
+/// Build the signing string `(request-target) host date digest` for a
+/// request, in the exact order `SIGNED_HEADERS` lists.
+fn signing_string(method: &str, path: &str, host: &str, date: &str, digest: &str) -> String {
+    format!(
+        "(request-target): {} {}\nhost: {}\ndate: {}\ndigest: {}",
+        method.to_lowercase(),
+        path,
+        host,
+        date,
+        digest
+    )
+}
+
+/// Sign `method`/`path`/`host`/`digest` with `key`, returning the value for
+/// a `Signature` header. The caller is responsible for attaching the
+/// `Date`/`Digest` headers it signed over.
+pub fn sign_request(
+    key: &SignatureKeyPair,
+    method: &str,
+    path: &str,
+    host: &str,
+    date: &str,
+    digest: &str,
+) -> Result<String, SignatureError> {
+    let string_to_sign = signing_string(method, path, host, date, digest);
+
+    let config = SigningConfig::new(Algorithm::Ed25519, &key.private_key, &key.key_id);
+    let signature = config
+        .sign(string_to_sign.as_bytes())
+        .map_err(|e| SignatureError::SigningFailed(e.to_string()))?;
+
+    Ok(format!(
+        "keyId=\"{}\",algorithm=\"ed25519\",headers=\"{}\",signature=\"{}\"",
+        key.key_id,
+        SIGNED_HEADERS,
+        base64::engine::general_purpose::STANDARD.encode(signature)
+    ))
+}
+
+/// One parsed `Signature` header's fields.
+struct ParsedSignature {
+    key_id: String,
+    signature: Vec<u8>,
+}
+
+fn parse_signature_header(header: &str) -> Result<ParsedSignature, SignatureError> {
+    let mut key_id = None;
+    let mut signature = None;
+
+    for field in header.split(',') {
+        let Some((name, value)) = field.split_once('=') else { continue };
+        let value = value.trim().trim_matches('"');
+        match name.trim() {
+            "keyId" => key_id = Some(value.to_string()),
+            "signature" => {
+                signature = Some(
+                    base64::engine::general_purpose::STANDARD
+                        .decode(value)
+                        .map_err(|_| SignatureError::MalformedSignature)?,
+                )
+            }
+            _ => {}
+        }
+    }
+
+    Ok(ParsedSignature {
+        key_id: key_id.ok_or(SignatureError::MalformedSignature)?,
+        signature: signature.ok_or(SignatureError::MalformedSignature)?,
+    })
+}
+
+/// Verify an incoming request's `Signature` header against `public_key`,
+/// rejecting a stale `Date` header (more than [`MAX_CLOCK_SKEW`] from now)
+/// to block replay of a captured request.
+pub fn verify_request(
+    public_key: &PublicKey,
+    method: &str,
+    path: &str,
+    host: &str,
+    date: &str,
+    digest: &str,
+    signature_header: &str,
+) -> Result<(), SignatureError> {
+    verify_date_freshness(date)?;
+
+    let parsed = parse_signature_header(signature_header)?;
+    let string_to_sign = signing_string(method, path, host, date, digest);
+
+    public_key
+        .verify(string_to_sign.as_bytes(), &parsed.signature, Algorithm::Ed25519)
+        .map_err(|_| SignatureError::VerificationFailed)
+}
+
+fn verify_date_freshness(date_header: &str) -> Result<(), SignatureError> {
+    let date = httpdate::parse_http_date(date_header).map_err(|_| SignatureError::StaleDate(MAX_CLOCK_SKEW))?;
+    let now = SystemTime::now();
+
+    let skew = if date > now { date.duration_since(now) } else { now.duration_since(date) };
+    let skew = skew.unwrap_or(Duration::MAX);
+
+    if skew > MAX_CLOCK_SKEW {
+        return Err(SignatureError::StaleDate(MAX_CLOCK_SKEW));
+    }
+
+    Ok(())
+}
+
+/// Current time formatted as an HTTP-date, for the `Date` header a signed
+/// request needs.
+pub fn http_date_now() -> String {
+    httpdate::fmt_http_date(UNIX_EPOCH + Duration::from_secs(
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+    ))
+}
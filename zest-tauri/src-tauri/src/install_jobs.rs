@@ -0,0 +1,349 @@
+//! Background job queue for installing/updating the proxy binary.
+//!
+//! Replaces the old single `download_progress` float with a tracked job
+//! that moves through `Queued -> Downloading -> Verifying -> Installing ->
+//! Done`/`Failed`, resumes a partially downloaded binary with an HTTP
+//! Range request after an interruption, verifies the completed file
+//! against the release's published SHA-256 checksum before it's swapped
+//! into place, and retries transient failures with exponential backoff.
+//! The legacy `ProxyStateInner::download_progress`/`is_downloading` fields
+//! (still read by `get_download_progress`/`is_downloading`) are kept in
+//! sync with the job's real byte-level progress for older callers.
+
+use crate::proxy::{GITHUB_REPO, ProxyState};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum InstallJobState {
+    Queued,
+    Downloading { bytes: u64, total: u64 },
+    Verifying,
+    Installing,
+    Done { version: String },
+    Failed { error: String },
+}
+
+#[derive(Clone, Serialize)]
+pub struct InstallJob {
+    pub id: String,
+    pub state: InstallJobState,
+}
+
+struct JobHandle {
+    job: InstallJob,
+    cancel: Arc<AtomicBool>,
+}
+
+static JOBS: Mutex<Option<HashMap<String, JobHandle>>> = Mutex::new(None);
+
+fn with_jobs<R>(f: impl FnOnce(&mut HashMap<String, JobHandle>) -> R) -> R {
+    let mut guard = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(HashMap::new))
+}
+
+fn set_state(app: &AppHandle, id: &str, state: InstallJobState) {
+    with_jobs(|jobs| {
+        if let Some(handle) = jobs.get_mut(id) {
+            handle.job.state = state.clone();
+        }
+    });
+    let _ = app.emit("install-job-update", InstallJob { id: id.to_string(), state });
+}
+
+/// Snapshot of every install job started in this process, including
+/// finished and failed ones (jobs are never removed from the list).
+pub fn get_install_jobs() -> Vec<InstallJob> {
+    with_jobs(|jobs| jobs.values().map(|h| h.job.clone()).collect())
+}
+
+/// Signal cancellation; the running download loop checks this between
+/// chunks and between retry attempts.
+pub fn cancel_install_job(id: &str) {
+    with_jobs(|jobs| {
+        if let Some(handle) = jobs.get(id) {
+            handle.cancel.store(true, Ordering::SeqCst);
+        }
+    });
+}
+
+/// Queue a new install job and return its id immediately; progress is
+/// reported via `install-job-update` events and `get_install_jobs`.
+/// `verify_checksums` gates whether a published checksum is actually
+/// checked; a release with no checksum asset is always a soft warning
+/// either way. `outbound_proxy` (see `proxy::resolve_outbound_proxy`) is
+/// used for the GitHub API call and the asset download itself.
+pub fn start(app: AppHandle, proxy_state: ProxyState, verify_checksums: bool, outbound_proxy: Option<String>) -> String {
+    let id = uuid::Uuid::new_v4().to_string();
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    with_jobs(|jobs| {
+        jobs.insert(
+            id.clone(),
+            JobHandle {
+                job: InstallJob {
+                    id: id.clone(),
+                    state: InstallJobState::Queued,
+                },
+                cancel: cancel.clone(),
+            },
+        );
+    });
+
+    let job_id = id.clone();
+    tauri::async_runtime::spawn(async move {
+        metrics::counter!("zest_downloads_total").increment(1);
+        let result = run(&app, &job_id, &proxy_state, &cancel, verify_checksums, outbound_proxy.as_deref()).await;
+        if result.is_err() {
+            let mut inner = proxy_state.inner.lock().await;
+            inner.is_downloading = false;
+        }
+        match result {
+            Ok(version) => {
+                metrics::counter!("zest_downloads_success_total").increment(1);
+                set_state(&app, &job_id, InstallJobState::Done { version })
+            }
+            Err(e) => {
+                metrics::counter!("zest_downloads_failed_total").increment(1);
+                set_state(&app, &job_id, InstallJobState::Failed { error: e })
+            }
+        }
+    });
+
+    id
+}
+
+async fn run(
+    app: &AppHandle,
+    job_id: &str,
+    proxy_state: &ProxyState,
+    cancel: &AtomicBool,
+    verify_checksums: bool,
+    outbound_proxy: Option<&str>,
+) -> Result<String, String> {
+    let client = crate::proxy::build_http_client(&crate::proxy::HttpClientConfig::with_proxy(outbound_proxy))
+        .map_err(|e| e.to_string())?;
+
+    let release_url = format!("https://api.github.com/repos/{}/releases/latest", GITHUB_REPO);
+    let release: crate::models::GitHubRelease = client
+        .get(&release_url)
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "Zest/1.0")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let asset = crate::proxy::find_compatible_asset(&release.assets)
+        .ok_or_else(|| "No compatible binary for this platform".to_string())?
+        .clone();
+
+    let expected_sha256 = if verify_checksums {
+        fetch_expected_checksum(&client, &release.assets, &asset.name).await
+    } else {
+        None
+    };
+    let has_checksum_asset = release.assets.iter().any(|a| {
+        let name = a.name.to_lowercase();
+        name.contains("checksum") || name.ends_with(".sha256")
+    });
+    if expected_sha256.is_none() && (verify_checksums || !has_checksum_asset) {
+        log::warn!("No published checksum found for {}; installing without verification", asset.name);
+    }
+
+    let download_dir = crate::proxy::ProxyStateInner::data_dir().join("downloads");
+    std::fs::create_dir_all(&download_dir).map_err(|e| e.to_string())?;
+    let partial_path = download_dir.join(format!("{}.part", asset.name));
+
+    {
+        let mut inner = proxy_state.inner.lock().await;
+        inner.is_downloading = true;
+        inner.download_progress = 0.0;
+    }
+
+    let data = download_with_resume(app, job_id, proxy_state, &client, &asset.browser_download_url, &partial_path, cancel).await?;
+
+    set_state(app, job_id, InstallJobState::Verifying);
+    if let Some(expected) = &expected_sha256 {
+        let found = sha256_hex(&data);
+        if &found != expected {
+            return Err(crate::proxy::ProxyError::ChecksumMismatch {
+                asset: asset.name.clone(),
+                expected: expected.clone(),
+                found,
+            }
+            .to_string());
+        }
+    }
+
+    set_state(app, job_id, InstallJobState::Installing);
+    crate::proxy::extract_and_install(&data)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = std::fs::remove_file(&partial_path);
+
+    {
+        let mut inner = proxy_state.inner.lock().await;
+        inner.download_progress = 1.0;
+        inner.is_downloading = false;
+    }
+
+    Ok(release.tag_name.strip_prefix('v').unwrap_or(&release.tag_name).to_string())
+}
+
+/// Find the checksum asset matching this release (commonly named
+/// `checksums.txt` or `<asset>.sha256`) and pull out the hash for our
+/// asset. Returns `None` if no checksum is published, in which case
+/// verification is skipped rather than failing the install outright.
+async fn fetch_expected_checksum(
+    client: &reqwest::Client,
+    assets: &[crate::models::GitHubAsset],
+    asset_name: &str,
+) -> Option<String> {
+    let own_checksum_name = format!("{}.sha256", asset_name).to_lowercase();
+    let checksum_asset = assets
+        .iter()
+        .find(|a| a.name.to_lowercase() == own_checksum_name)
+        .or_else(|| assets.iter().find(|a| a.name.to_lowercase().contains("checksum")))?;
+
+    let body = client
+        .get(&checksum_asset.browser_download_url)
+        .header("User-Agent", "Zest/1.0")
+        .send()
+        .await
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+
+    // Typical `sha256sum` output: "<hash>  <filename>" per line.
+    for line in body.lines() {
+        let mut parts = line.split_whitespace();
+        if let (Some(hash), Some(name)) = (parts.next(), parts.next()) {
+            if name.trim_start_matches('*') == asset_name {
+                return Some(hash.to_lowercase());
+            }
+        }
+    }
+
+    // Single-hash file named after the asset (e.g. `<asset>.sha256`).
+    if checksum_asset.name.to_lowercase() != own_checksum_name {
+        return None;
+    }
+    body.split_whitespace().next().map(|h| h.to_lowercase())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Download `url` into `partial_path`, resuming from whatever is already
+/// on disk via an HTTP Range request, retrying transient failures with
+/// exponential backoff. Returns the complete file's bytes.
+async fn download_with_resume(
+    app: &AppHandle,
+    job_id: &str,
+    proxy_state: &ProxyState,
+    client: &reqwest::Client,
+    url: &str,
+    partial_path: &std::path::Path,
+    cancel: &AtomicBool,
+) -> Result<Vec<u8>, String> {
+    let mut backoff = INITIAL_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        if cancel.load(Ordering::SeqCst) {
+            return Err("Install job canceled".to_string());
+        }
+
+        match try_download(app, job_id, proxy_state, client, url, partial_path, cancel).await {
+            Ok(data) => return Ok(data),
+            Err(e) if attempt == MAX_ATTEMPTS => return Err(e),
+            Err(e) => {
+                log::warn!("Download attempt {} failed: {}, retrying in {:?}", attempt, e, backoff);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    }
+
+    unreachable!("loop always returns on the final attempt")
+}
+
+async fn try_download(
+    app: &AppHandle,
+    job_id: &str,
+    proxy_state: &ProxyState,
+    client: &reqwest::Client,
+    url: &str,
+    partial_path: &std::path::Path,
+    cancel: &AtomicBool,
+) -> Result<Vec<u8>, String> {
+    use futures_util::StreamExt;
+    use std::io::Write;
+
+    let already_downloaded = std::fs::metadata(partial_path).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url).header("User-Agent", "Zest/1.0");
+    if already_downloaded > 0 {
+        request = request.header("Range", format!("bytes={}-", already_downloaded));
+    }
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { already_downloaded } else { 0 };
+
+    let content_length = response.content_length().unwrap_or(0);
+    let total = if resumed { downloaded + content_length } else { content_length };
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(partial_path)
+        .map_err(|e| e.to_string())?;
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        if cancel.load(Ordering::SeqCst) {
+            return Err("Install job canceled".to_string());
+        }
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        file.write_all(&chunk).map_err(|e| e.to_string())?;
+        downloaded += chunk.len() as u64;
+        set_state(
+            app,
+            job_id,
+            InstallJobState::Downloading {
+                bytes: downloaded,
+                total,
+            },
+        );
+        if total > 0 {
+            proxy_state.inner.lock().await.download_progress = downloaded as f64 / total as f64;
+        }
+    }
+
+    if total > 0 && downloaded < total {
+        // Connection dropped short; leave the partial file in place so the
+        // next attempt resumes from here instead of installing a truncated
+        // binary.
+        return Err(format!("download interrupted at {} of {} bytes", downloaded, total));
+    }
+
+    std::fs::read(partial_path).map_err(|e| e.to_string())
+}
@@ -0,0 +1,135 @@
+//! One-shot IPC link between the running GUI and the standalone `zest` CLI
+//! binary (`src/bin/zest.rs`).
+//!
+//! On startup the GUI spawns [`serve`] in a background thread: it opens an
+//! `ipc_channel::ipc::IpcOneShotServer`, writes the server name to
+//! `paths::ipc_server_file()`, and blocks for a single request. The CLI
+//! reads that file, connects, sends an [`IpcRequest`] carrying its own
+//! one-shot reply channel, and blocks on the reply. After handling one
+//! request the GUI opens a fresh server and repeats, so each CLI
+//! invocation gets its own exchange. If the CLI can't connect (no file, or
+//! the GUI isn't running), it falls back to the same pure filesystem
+//! helpers below directly — see `apply_agent`/`remove_agent`/`env_command`.
+
+use crate::agent_registry;
+use crate::models::ProxyStatus;
+use crate::proxy::{self, ProxyState};
+use crate::shell_profile;
+use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IpcRequest {
+    Start,
+    Stop,
+    Status,
+    Env { agent: String, api_key: Option<String> },
+    ApplyAgent { agent: String, shell: Option<String>, api_key: Option<String> },
+    RemoveAgent { agent: String, shell: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IpcResponse {
+    Status(ProxyStatus),
+    Env(String),
+    Ok,
+    Error(String),
+}
+
+type Envelope = (IpcRequest, IpcSender<IpcResponse>);
+
+/// Run the one-shot server loop forever in a dedicated thread, handling one
+/// `zest` CLI invocation per iteration.
+pub fn serve(app: tauri::AppHandle, proxy_state: ProxyState) {
+    std::thread::spawn(move || loop {
+        if let Err(e) = accept_one(&app, &proxy_state) {
+            log::warn!("zest CLI IPC server error: {}", e);
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+    });
+}
+
+fn accept_one(app: &tauri::AppHandle, proxy_state: &ProxyState) -> Result<(), String> {
+    let (request_server, server_name) =
+        IpcOneShotServer::<Envelope>::new().map_err(|e| e.to_string())?;
+    write_server_name(&server_name)?;
+
+    let (_rx, (request, reply_tx)) = request_server.accept().map_err(|e| e.to_string())?;
+    let response = tauri::async_runtime::block_on(handle_request(app, request, proxy_state));
+    let _ = reply_tx.send(response);
+    Ok(())
+}
+
+fn write_server_name(name: &str) -> Result<(), String> {
+    let path = crate::paths::ipc_server_file();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, name).map_err(|e| e.to_string())
+}
+
+async fn handle_request(app: &tauri::AppHandle, request: IpcRequest, proxy_state: &ProxyState) -> IpcResponse {
+    match request {
+        IpcRequest::Start => match proxy::start_proxy(app, &proxy_state.inner, proxy::resolve_outbound_proxy(None).as_deref(), None).await {
+            Ok(status) => IpcResponse::Status(status),
+            Err(e) => IpcResponse::Error(e.to_string()),
+        },
+        IpcRequest::Stop => match proxy::stop_proxy(&proxy_state.inner).await {
+            Ok(status) => IpcResponse::Status(status),
+            Err(e) => IpcResponse::Error(e.to_string()),
+        },
+        IpcRequest::Status => {
+            let inner = proxy_state.inner.lock().await;
+            IpcResponse::Status(inner.status.clone())
+        }
+        IpcRequest::Env { agent, api_key } => {
+            let port = proxy_state.inner.lock().await.status.port;
+            match env_command(&agent, api_key.as_deref(), port) {
+                Ok(cmd) => IpcResponse::Env(cmd),
+                Err(e) => IpcResponse::Error(e),
+            }
+        }
+        IpcRequest::ApplyAgent { agent, shell, api_key } => {
+            let port = proxy_state.inner.lock().await.status.port;
+            match apply_agent(&agent, shell.as_deref(), api_key.as_deref(), port) {
+                Ok(()) => IpcResponse::Ok,
+                Err(e) => IpcResponse::Error(e),
+            }
+        }
+        IpcRequest::RemoveAgent { agent, shell } => match remove_agent(&agent, shell.as_deref()) {
+            Ok(()) => IpcResponse::Ok,
+            Err(e) => IpcResponse::Error(e),
+        },
+    }
+}
+
+/// Resolve `shell`, falling back to the detected shell when absent. Shared
+/// by the GUI-side IPC handler and the CLI's direct fallback path, since
+/// both ultimately just mutate the shell profile.
+fn resolve_shell(shell: Option<&str>) -> Result<shell_profile::ShellType, String> {
+    match shell {
+        Some(s) => shell_profile::parse_shell_type(s),
+        None => Ok(shell_profile::detect_shell()),
+    }
+}
+
+/// `zest env <agent>` — the same line `get_env_command` renders.
+pub fn env_command(agent: &str, api_key: Option<&str>, port: u16) -> Result<String, String> {
+    let manifest = agent_registry::find(agent).map_err(|e| e.to_string())?;
+    Ok(manifest.render_env_command(shell_profile::detect_shell(), port, api_key))
+}
+
+/// `zest apply <agent> [--shell zsh]` — the same profile edit `configure_agent` performs.
+pub fn apply_agent(agent: &str, shell: Option<&str>, api_key: Option<&str>, port: u16) -> Result<(), String> {
+    let manifest = agent_registry::find(agent).map_err(|e| e.to_string())?;
+    let shell_type = resolve_shell(shell)?;
+    let config = manifest.render_profile_config(shell_type, port, api_key);
+    shell_profile::add_to_profile(shell_type, &manifest.name, &config).map_err(|e| e.to_string())
+}
+
+/// `zest remove <agent>` — the same profile edit `unconfigure_agent` performs.
+pub fn remove_agent(agent: &str, shell: Option<&str>) -> Result<(), String> {
+    let manifest = agent_registry::find(agent).map_err(|e| e.to_string())?;
+    let shell_type = resolve_shell(shell)?;
+    shell_profile::remove_from_profile(shell_type, &manifest.name).map_err(|e| e.to_string())
+}
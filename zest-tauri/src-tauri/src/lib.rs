@@ -1,9 +1,32 @@
 //! Zest library entry point for Tauri
 
+pub mod agent_registry;
+pub mod archive;
 pub mod commands;
+pub mod config_watcher;
+pub mod credential_cache;
+pub mod credential_process;
+pub mod ipc;
+pub mod oidc;
+pub mod onepassword;
+pub mod paths;
 pub mod proxy;
 pub mod tray;
 pub mod settings;
 pub mod credentials;
 pub mod models;
+pub mod secret_string;
 pub mod shell_profile;
+pub mod terminal_launch;
+pub mod auth_crypto;
+pub mod clipboard;
+pub mod environment;
+pub mod http_signature;
+pub mod install_jobs;
+pub mod log_stream;
+pub mod management_client;
+pub mod metrics;
+pub mod policy;
+pub mod shims;
+pub mod updater;
+pub mod vault;
@@ -0,0 +1,195 @@
+//! Real-time log streaming via Tauri events.
+//!
+//! `fetch_logs` makes the frontend re-poll `/logs?after=` on a fixed
+//! timer. This wraps the same endpoint in a tight background loop per
+//! subscriber: each new line is parsed into a [`LogEntry`] and emitted
+//! individually as a `log-line` event, with the last [`BACKFILL_LINES`]
+//! sent immediately on subscribe and per-level filtering applied before a
+//! line ever reaches the frontend. A fetch failure triggers exponential
+//! backoff and a `log-stream-status` event rather than tearing the stream
+//! down.
+
+use crate::models::{LogEntry, LogLevel, LogsResponse};
+use crate::proxy::ProxyState;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::task::JoinHandle;
+
+/// How many backlog lines to emit immediately on subscribe.
+const BACKFILL_LINES: usize = 50;
+/// Steady-state poll interval while the proxy is reachable.
+const POLL_INTERVAL: Duration = Duration::from_millis(750);
+/// Backoff cap while the proxy is unreachable.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+static STREAMS: Mutex<Option<HashMap<String, JoinHandle<()>>>> = Mutex::new(None);
+
+#[derive(Clone, serde::Serialize)]
+struct LogLinePayload {
+    stream_id: String,
+    entry: LogEntry,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum StreamStatus {
+    Connected,
+    Reconnecting,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct StreamStatusPayload {
+    stream_id: String,
+    status: StreamStatus,
+}
+
+/// Start tailing the proxy's logs for one subscriber. Returns a stream id
+/// that `stop` uses to tear the background task down.
+pub fn start(app: AppHandle, proxy_state: ProxyState, min_level: Option<LogLevel>) -> String {
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    let task_stream_id = stream_id.clone();
+
+    let handle = tauri::async_runtime::spawn(async move {
+        run(app, proxy_state, task_stream_id, min_level).await;
+    });
+
+    let mut streams = STREAMS.lock().unwrap_or_else(|e| e.into_inner());
+    streams.get_or_insert_with(HashMap::new).insert(stream_id.clone(), handle);
+    stream_id
+}
+
+/// Stop a previously started stream.
+pub fn stop(stream_id: &str) {
+    let mut streams = STREAMS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(map) = streams.as_mut() {
+        if let Some(handle) = map.remove(stream_id) {
+            handle.abort();
+        }
+    }
+}
+
+async fn run(app: AppHandle, proxy_state: ProxyState, stream_id: String, min_level: Option<LogLevel>) {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap_or_default();
+
+    let mut last_timestamp: Option<i64> = None;
+    let mut backoff = POLL_INTERVAL;
+    let mut backfilled = false;
+
+    loop {
+        let (management_url, management_key, running) = {
+            let inner = proxy_state.inner.lock().await;
+            (inner.management_url(), inner.management_key.clone(), inner.status.running)
+        };
+
+        if !running {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+
+        let mut url = format!("{}/logs", management_url);
+        if let Some(after) = last_timestamp {
+            url = format!("{}?after={}", url, after);
+        }
+
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", management_key))
+            .send()
+            .await
+            .ok()
+            .filter(|r| r.status().is_success());
+
+        let logs = match response {
+            Some(r) => r.json::<LogsResponse>().await.ok(),
+            None => None,
+        };
+
+        match logs {
+            Some(logs) => {
+                backoff = POLL_INTERVAL;
+                last_timestamp = logs.latest_timestamp.or(last_timestamp);
+
+                let mut lines = logs.lines.unwrap_or_default();
+                if !backfilled {
+                    let skip = lines.len().saturating_sub(BACKFILL_LINES);
+                    lines = lines.split_off(skip);
+                    backfilled = true;
+                }
+
+                for line in lines {
+                    let entry = parse_log_line(&line);
+                    if min_level.is_some_and(|min| !meets_level(entry.level, min)) {
+                        continue;
+                    }
+                    let _ = app.emit(
+                        "log-line",
+                        LogLinePayload {
+                            stream_id: stream_id.clone(),
+                            entry,
+                        },
+                    );
+                }
+            }
+            None => {
+                let _ = app.emit(
+                    "log-stream-status",
+                    StreamStatusPayload {
+                        stream_id: stream_id.clone(),
+                        status: StreamStatus::Reconnecting,
+                    },
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Whether `level` is at least as severe as `min` (Debug < Info < Warn < Error).
+fn meets_level(level: LogLevel, min: LogLevel) -> bool {
+    severity(level) >= severity(min)
+}
+
+fn severity(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Debug => 0,
+        LogLevel::Info => 1,
+        LogLevel::Warn => 2,
+        LogLevel::Error => 3,
+    }
+}
+
+/// Best-effort parse of a raw proxy log line into a [`LogEntry`]. The
+/// proxy's log format isn't machine-structured, so this only extracts a
+/// level keyword and falls back to treating the whole line as the message.
+fn parse_log_line(line: &str) -> LogEntry {
+    let level = if line.contains("ERROR") || line.contains("error") {
+        LogLevel::Error
+    } else if line.contains("WARN") || line.contains("warn") {
+        LogLevel::Warn
+    } else if line.contains("DEBUG") || line.contains("debug") {
+        LogLevel::Debug
+    } else {
+        LogLevel::Info
+    };
+
+    LogEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        level,
+        message: line.to_string(),
+        source: None,
+        status_code: None,
+        model: None,
+        provider: None,
+        duration_ms: None,
+    }
+}
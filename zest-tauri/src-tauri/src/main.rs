@@ -5,13 +5,36 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod agent_registry;
+mod archive;
+mod auth_crypto;
+mod clipboard;
 mod commands;
+mod config_watcher;
+mod credential_cache;
+mod credential_process;
 mod proxy;
 mod tray;
 mod settings;
 mod credentials;
+mod environment;
+mod http_signature;
+mod install_jobs;
+mod ipc;
+mod log_stream;
+mod management_client;
+mod metrics;
 mod models;
+mod oidc;
+mod onepassword;
+mod paths;
+mod policy;
+mod secret_string;
 mod shell_profile;
+mod shims;
+mod terminal_launch;
+mod updater;
+mod vault;
 
 use tauri::Manager;
 
@@ -32,11 +55,27 @@ fn main() {
 
             // Initialize proxy manager state
             let proxy_state = proxy::ProxyState::new();
-            app.manage(proxy_state);
+            app.manage(proxy_state.clone());
+
+            // Serve the `zest` CLI's one-shot IPC requests in the background
+            ipc::serve(app.handle().clone(), proxy_state.clone());
+
+            // Hot-reload config.yaml and the auth-files directory on external edits
+            config_watcher::watch(app.handle().clone());
 
             // Initialize settings
             let settings_state = settings::SettingsState::new();
+            let settings_inner = settings_state.inner.clone();
+            let metrics_port = settings_inner.blocking_lock().metrics_port;
             app.manage(settings_state);
+            settings::watch_settings_file(app.handle().clone(), settings_inner);
+
+            // Start the Prometheus metrics recorder, HTTP endpoint, and
+            // background poll loop
+            if let Err(e) = metrics::install_recorder() {
+                log::warn!("{}", e);
+            }
+            metrics::start(proxy_state, metrics_port);
 
             log::info!("Zest application started successfully");
 
@@ -47,17 +86,28 @@ fn main() {
             commands::start_proxy,
             commands::stop_proxy,
             commands::get_proxy_status,
+            commands::get_proxy_logs,
             commands::install_proxy_binary,
             commands::get_proxy_version,
+            commands::check_proxy_compatibility,
+            commands::check_for_proxy_binary_update,
             commands::get_proxy_api_key,
             commands::get_provider_api_key,
             commands::is_binary_installed,
             commands::get_download_progress,
             commands::is_downloading,
+            commands::get_install_jobs,
+            commands::cancel_install_job,
 
             // Settings commands
             commands::get_settings,
             commands::save_settings,
+            commands::get_settings_schema,
+            commands::list_settings_profiles,
+            commands::get_active_settings_profile,
+            commands::save_settings_profile,
+            commands::activate_settings_profile,
+            commands::delete_settings_profile,
             commands::get_port,
             commands::set_port,
 
@@ -75,27 +125,48 @@ fn main() {
             commands::get_api_keys,
             commands::add_api_key,
             commands::delete_api_key,
+            commands::check_key_authorized,
 
             // Logs commands
             commands::fetch_logs,
             commands::clear_logs,
+            commands::start_log_stream,
+            commands::stop_log_stream,
             commands::fetch_usage,
             commands::fetch_request_history,
             commands::clear_request_history,
 
+            // Metrics commands
+            commands::get_metrics,
+
+            // Updater commands
+            commands::check_for_update,
+            commands::apply_update,
+
+            // OIDC commands
+            commands::start_oidc_login,
+            commands::finish_oidc_login,
+
             // Credentials commands
             commands::store_credential,
             commands::get_credential,
             commands::delete_credential,
+            commands::set_master_passphrase,
+            commands::unlock_vault,
+            commands::lock_vault,
+            commands::is_vault_unlocked,
 
             // OAuth commands
             commands::start_oauth_flow,
             commands::check_oauth_status,
+            commands::cancel_oauth_flow,
 
             // System commands
             commands::open_config_folder,
             commands::open_logs_folder,
             commands::copy_to_clipboard,
+            commands::read_from_clipboard,
+            commands::copy_image_to_clipboard,
 
             // Shell Profile commands
             commands::detect_shell,
@@ -107,12 +178,19 @@ fn main() {
             commands::get_env_command,
             commands::get_available_shells,
             commands::get_available_agents,
+            commands::install_agent_manifest,
+            commands::list_agent_manifests,
+            commands::remove_agent_manifest,
+            commands::launch_agent,
 
             // Advanced Agent Configuration
             commands::find_agent_binary,
             commands::configure_agent_advanced,
             commands::get_agent_backups,
             commands::restore_agent_backup,
+            commands::list_agent_shims,
+            commands::remove_agent_shims,
+            commands::diagnose_environment,
 
             // Direct Auth File Commands (when proxy is not running)
             commands::scan_auth_files_direct,
@@ -120,6 +198,7 @@ fn main() {
             commands::create_auth_file,
             commands::delete_auth_file_direct,
             commands::toggle_auth_file_direct,
+            commands::migrate_auth_files_to_encrypted,
         ])
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
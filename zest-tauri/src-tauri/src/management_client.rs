@@ -0,0 +1,208 @@
+//! Typed client for the proxy's management API.
+//!
+//! Centralizes base URL + auth header construction and response handling
+//! behind typed async methods, so the command layer becomes thin wrappers
+//! instead of hand-building URLs and duplicating JSON parsing and error
+//! handling for every endpoint.
+
+use crate::http_signature::{self, SignatureKeyPair};
+use crate::models::{AuthFileModel, AuthFileModelsResponse, AuthFilesResponse, ApiKeysResponse, LogsResponse, OAuthUrlResponse, QuotaInfo, UsageStats};
+use crate::proxy::{ProxyStateInner, ProxyVersionInfo, CLIENT_VERSION};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ManagementError {
+    #[error("request to proxy failed: {0}")]
+    Request(String),
+    #[error("proxy returned {status}: {message}")]
+    Status { status: u16, message: String },
+}
+
+impl ManagementError {
+    fn request(e: reqwest::Error) -> Self {
+        Self::Request(e.to_string())
+    }
+}
+
+/// Owns the base URL and management key for one proxy instance, so every
+/// call attaches the same `Authorization`/`X-Zest-Version` headers without
+/// re-deriving them (and without a fresh `reqwest::Client` per call).
+pub struct ManagementApiClient {
+    client: reqwest::Client,
+    base_url: String,
+    host: String,
+    management_key: String,
+    /// Present when `config.yaml`'s `remote-management.oauth-signature-keys`
+    /// points at a loadable Ed25519 key pair, so requests also carry a
+    /// `Signature` header (see `http_signature.rs`) on top of the shared
+    /// `management_key`.
+    signing_key: Option<Arc<SignatureKeyPair>>,
+}
+
+impl ManagementApiClient {
+    /// Snapshot the base URL and management key out of `ProxyStateInner`.
+    /// Taking owned copies rather than borrowing lets callers drop the
+    /// `ProxyStateInner` lock before awaiting the request. Also loads the
+    /// HTTP signature key pair from `config.yaml`, if one is configured,
+    /// tolerating a missing/unparsable config since signing is optional.
+    pub fn new(inner: &ProxyStateInner) -> Self {
+        let base_url = inner.management_url();
+        let host = reqwest::Url::parse(&base_url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+            .unwrap_or_default();
+
+        let signing_key = crate::config_watcher::load_config(&ProxyStateInner::config_path())
+            .ok()
+            .and_then(|config| config.remote_management.oauth_signature_keys)
+            .and_then(|keys| {
+                SignatureKeyPair::load(keys.key_id, Path::new(&keys.private_key_path))
+                    .map_err(|e| log::warn!("Failed to load HTTP signature key pair: {}", e))
+                    .ok()
+            })
+            .map(Arc::new);
+
+        Self {
+            client: reqwest::Client::new(),
+            base_url,
+            host,
+            management_key: inner.management_key.clone(),
+            signing_key,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.signed_request(method, path, b"")
+    }
+
+    /// Like [`Self::request`], but also attaches `Digest`/`Date`/`Signature`
+    /// headers computed over `body` when a signing key is configured.
+    fn signed_request(&self, method: reqwest::Method, path: &str, body: &[u8]) -> reqwest::RequestBuilder {
+        let mut builder = self
+            .client
+            .request(method.clone(), format!("{}{}", self.base_url, path))
+            .header("Authorization", format!("Bearer {}", self.management_key))
+            .header("X-Zest-Version", CLIENT_VERSION);
+
+        if let Some(key) = &self.signing_key {
+            let date = http_signature::http_date_now();
+            let digest = http_signature::digest_header(body);
+            match http_signature::sign_request(key, method.as_str(), path, &self.host, &date, &digest) {
+                Ok(signature) => {
+                    builder = builder.header("Digest", digest).header("Date", date).header("Signature", signature);
+                }
+                Err(e) => log::warn!("Failed to sign management API request: {}", e),
+            }
+        }
+
+        builder
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, builder: reqwest::RequestBuilder) -> Result<T, ManagementError> {
+        let response = builder.send().await.map_err(ManagementError::request)?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(ManagementError::Status { status: status.as_u16(), message });
+        }
+        response.json().await.map_err(ManagementError::request)
+    }
+
+    async fn send_unit(&self, builder: reqwest::RequestBuilder) -> Result<(), ManagementError> {
+        let response = builder.send().await.map_err(ManagementError::request)?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(ManagementError::Status { status: status.as_u16(), message });
+        }
+        Ok(())
+    }
+
+    pub async fn version(&self) -> Result<ProxyVersionInfo, ManagementError> {
+        self.send_json(self.request(reqwest::Method::GET, "/version")).await
+    }
+
+    pub async fn auth_files(&self) -> Result<AuthFilesResponse, ManagementError> {
+        self.send_json(self.request(reqwest::Method::GET, "/auth-files")).await
+    }
+
+    pub async fn delete_auth_file(&self, name: &str) -> Result<(), ManagementError> {
+        let encoded = urlencoding::encode(name);
+        self.send_unit(self.request(reqwest::Method::DELETE, &format!("/auth-files?name={}", encoded))).await
+    }
+
+    pub async fn toggle_auth_file(&self, file_id: &str, disabled: bool) -> Result<(), ManagementError> {
+        let body = serde_json::json!({ "disabled": disabled });
+        let bytes = serde_json::to_vec(&body).unwrap_or_default();
+        let path = format!("/auth-files/{}/toggle", file_id);
+        let builder = self.signed_request(reqwest::Method::POST, &path, &bytes).json(&body);
+        self.send_unit(builder).await
+    }
+
+    pub async fn auth_file_models(&self, name: &str) -> Result<Vec<AuthFileModel>, ManagementError> {
+        let encoded = urlencoding::encode(name);
+        let response: AuthFileModelsResponse = self
+            .send_json(self.request(reqwest::Method::GET, &format!("/auth-files/models?name={}", encoded)))
+            .await?;
+        Ok(response.models)
+    }
+
+    pub async fn quota(&self, provider: &str, account: &str) -> Result<QuotaInfo, ManagementError> {
+        self.send_json(self.request(reqwest::Method::GET, &format!("/quota/{}/{}", provider, account)))
+            .await
+    }
+
+    pub async fn all_quotas(&self) -> Result<Vec<QuotaInfo>, ManagementError> {
+        self.send_json(self.request(reqwest::Method::GET, "/quotas")).await
+    }
+
+    pub async fn api_keys(&self) -> Result<Vec<String>, ManagementError> {
+        let response: ApiKeysResponse = self.send_json(self.request(reqwest::Method::GET, "/api-keys")).await?;
+        Ok(response.api_keys)
+    }
+
+    pub async fn add_api_key(&self, key: &str) -> Result<(), ManagementError> {
+        let body = serde_json::json!({ "key": key });
+        let bytes = serde_json::to_vec(&body).unwrap_or_default();
+        let builder = self.signed_request(reqwest::Method::POST, "/api-keys", &bytes).json(&body);
+        self.send_unit(builder).await
+    }
+
+    pub async fn delete_api_key(&self, key: &str) -> Result<(), ManagementError> {
+        let encoded = urlencoding::encode(key);
+        self.send_unit(self.request(reqwest::Method::DELETE, &format!("/api-keys/{}", encoded))).await
+    }
+
+    pub async fn logs(&self, after_timestamp: Option<i64>) -> Result<LogsResponse, ManagementError> {
+        let path = match after_timestamp {
+            Some(after) => format!("/logs?after={}", after),
+            None => "/logs".to_string(),
+        };
+        self.send_json(self.request(reqwest::Method::GET, &path)).await
+    }
+
+    pub async fn clear_logs(&self) -> Result<(), ManagementError> {
+        self.send_unit(self.request(reqwest::Method::DELETE, "/logs")).await
+    }
+
+    pub async fn usage(&self) -> Result<serde_json::Value, ManagementError> {
+        self.send_json(self.request(reqwest::Method::GET, "/usage")).await
+    }
+
+    /// Same `/usage` endpoint as [`Self::usage`], typed as a per-provider
+    /// breakdown keyed by `AIProvider::raw_value()` for callers (like the
+    /// metrics exporter) that want `UsageStats` rather than raw JSON.
+    pub async fn usage_stats(&self) -> Result<std::collections::HashMap<String, UsageStats>, ManagementError> {
+        self.send_json(self.request(reqwest::Method::GET, "/usage")).await
+    }
+
+    /// `endpoint` is the provider-specific auth-url path (e.g.
+    /// `/anthropic-auth-url?is_webui=true`), since providers differ on
+    /// whether they support WebUI OAuth at all.
+    pub async fn oauth_url(&self, endpoint: &str) -> Result<OAuthUrlResponse, ManagementError> {
+        self.send_json(self.request(reqwest::Method::GET, endpoint)).await
+    }
+}
@@ -0,0 +1,257 @@
+//! Prometheus metrics exporter for proxy traffic, provider quotas, and the
+//! health of the proxy/updater's own background work.
+//!
+//! Registers a `metrics`-crate recorder at startup, serves `/metrics` on a
+//! small dedicated HTTP listener, and is kept fresh two ways: a background
+//! poll loop that reuses the same management-API data `fetch_usage`,
+//! `fetch_all_quotas`, and `fetch_request_history` already fetch, plus
+//! counters/gauges/histograms recorded directly at their call sites in
+//! `proxy`, `install_jobs`, and `updater` (downloads, extraction failures,
+//! management-API latencies/status codes, auth-file availability, and the
+//! supervisor's health state) since those only make sense to record as the
+//! events happen rather than by re-polling.
+
+use crate::models::{QuotaInfo, RequestHistoryEntry, UsageStats};
+use crate::proxy::ProxyState;
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+static RECORDER_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Ids of request-history entries already folded into the counters, so a
+/// poll that re-reads the same 50-entry window doesn't double-count them.
+static SEEN_ENTRY_IDS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// Install the global Prometheus recorder. Must be called exactly once,
+/// before any `metrics::counter!`/`gauge!`/`histogram!` calls, and before
+/// [`render`] is first used.
+pub fn install_recorder() -> Result<(), String> {
+    let handle = PrometheusBuilder::new()
+        .install_recorder()
+        .map_err(|e| format!("Failed to install Prometheus recorder: {}", e))?;
+    RECORDER_HANDLE
+        .set(handle)
+        .map_err(|_| "Prometheus recorder already installed".to_string())
+}
+
+/// Render the current metrics snapshot in Prometheus text-exposition format.
+pub fn render() -> String {
+    RECORDER_HANDLE.get().map(|h| h.render()).unwrap_or_default()
+}
+
+/// Start the `/metrics` HTTP endpoint and the background poll loop that
+/// keeps its counters and gauges up to date. Both run for the lifetime of
+/// the app; a failed poll is logged and skipped rather than aborting.
+pub fn start(proxy_state: ProxyState, metrics_port: u16) {
+    tauri::async_runtime::spawn(serve_http(metrics_port));
+    tauri::async_runtime::spawn(poll_loop(proxy_state));
+}
+
+async fn serve_http(port: u16) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::warn!("Failed to bind metrics endpoint on port {}: {}", port, e);
+            return;
+        }
+    };
+
+    log::info!("Serving Prometheus metrics on http://127.0.0.1:{}/metrics", port);
+
+    loop {
+        let (mut socket, _) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                log::warn!("Metrics listener accept failed: {}", e);
+                continue;
+            }
+        };
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            // We only ever serve one fixed body, so the request itself
+            // (method, path, headers) doesn't need to be parsed.
+            if socket.read(&mut buf).await.is_err() {
+                return;
+            }
+
+            let body = render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// How often the poll loop refreshes metrics from the management API.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+async fn poll_loop(proxy_state: ProxyState) {
+    loop {
+        if let Err(e) = poll_once(&proxy_state).await {
+            log::debug!("Metrics poll skipped: {}", e);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn poll_once(proxy_state: &ProxyState) -> Result<(), String> {
+    let (management_url, management_key, running) = {
+        let inner = proxy_state.inner.lock().await;
+        (inner.management_url(), inner.management_key.clone(), inner.status.running)
+    };
+
+    if !running {
+        return Ok(());
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    record_quotas(&client, &management_url, &management_key).await?;
+    record_usage_stats(&client, &management_url, &management_key).await?;
+    record_request_history()?;
+
+    Ok(())
+}
+
+async fn record_quotas(client: &reqwest::Client, management_url: &str, management_key: &str) -> Result<(), String> {
+    let response = client
+        .get(format!("{}/quotas", management_url))
+        .header("Authorization", format!("Bearer {}", management_key))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Ok(());
+    }
+
+    let quotas: Vec<QuotaInfo> = response.json().await.map_err(|e| e.to_string())?;
+    for quota in quotas {
+        gauge!(
+            "zest_quota_used",
+            "provider" => quota.provider.clone(),
+            "account" => quota.account.clone()
+        )
+        .set(quota.used as f64);
+        gauge!(
+            "zest_quota_limit",
+            "provider" => quota.provider.clone(),
+            "account" => quota.account.clone()
+        )
+        .set(quota.limit as f64);
+        gauge!(
+            "zest_quota_percentage",
+            "provider" => quota.provider,
+            "account" => quota.account
+        )
+        .set(quota.percentage_used());
+    }
+
+    Ok(())
+}
+
+/// Record the proxy's own running totals (per `AIProvider::raw_value()`) as
+/// absolute counters, so a Prometheus scrape sees the same cumulative
+/// numbers the proxy reports rather than re-deriving them from request
+/// history.
+async fn record_usage_stats(client: &reqwest::Client, management_url: &str, management_key: &str) -> Result<(), String> {
+    let response = client
+        .get(format!("{}/usage", management_url))
+        .header("Authorization", format!("Bearer {}", management_key))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Ok(());
+    }
+
+    let stats: std::collections::HashMap<String, UsageStats> =
+        response.json().await.map_err(|e| e.to_string())?;
+
+    for (provider, usage) in stats {
+        if let Some(total) = usage.total_requests {
+            counter!("zest_provider_requests_total", "provider" => provider.clone()).absolute(total.max(0) as u64);
+        }
+        if let Some(success) = usage.success_count {
+            counter!("zest_provider_requests_success_total", "provider" => provider.clone())
+                .absolute(success.max(0) as u64);
+        }
+        if let Some(failure) = usage.failure_count {
+            counter!("zest_provider_requests_failure_total", "provider" => provider.clone())
+                .absolute(failure.max(0) as u64);
+        }
+        if let Some(tokens) = usage.total_tokens {
+            counter!("zest_provider_tokens_total", "provider" => provider).absolute(tokens.max(0) as u64);
+        }
+    }
+
+    Ok(())
+}
+
+fn record_request_history() -> Result<(), String> {
+    let entries: Vec<RequestHistoryEntry> = crate::commands::fetch_request_history()?;
+    let seen = SEEN_ENTRY_IDS.get_or_init(|| Mutex::new(HashSet::new()));
+    let mut seen = seen.lock().map_err(|e| e.to_string())?;
+
+    // fetch_request_history() always returns the latest window, so entries
+    // we've already folded into the counters would otherwise be recounted
+    // on every poll.
+    if seen.len() > 1000 {
+        let current: HashSet<&String> = entries.iter().map(|e| &e.id).collect();
+        seen.retain(|id| current.contains(id));
+    }
+
+    for entry in entries {
+        if !seen.insert(entry.id.clone()) {
+            continue;
+        }
+
+        let provider = entry.provider.clone().unwrap_or_else(|| "unknown".to_string());
+        let model = entry.model.clone().unwrap_or_else(|| "unknown".to_string());
+
+        counter!(
+            "zest_requests_total",
+            "provider" => provider.clone(),
+            "model" => model.clone()
+        )
+        .increment(1);
+        // RequestHistoryEntry only records transferred bytes, not token
+        // counts, so bytes stand in for "token totals" here.
+        counter!(
+            "zest_request_bytes_total",
+            "provider" => provider.clone(),
+            "model" => model.clone(),
+            "direction" => "request"
+        )
+        .increment(entry.request_size.max(0) as u64);
+        counter!(
+            "zest_request_bytes_total",
+            "provider" => provider.clone(),
+            "model" => model.clone(),
+            "direction" => "response"
+        )
+        .increment(entry.response_size.max(0) as u64);
+        histogram!(
+            "zest_request_duration_ms",
+            "provider" => provider,
+            "model" => model
+        )
+        .record(entry.duration_ms as f64);
+    }
+
+    Ok(())
+}
@@ -2,7 +2,11 @@
 //!
 //! These models mirror the Swift models from the original Zest app.
 
+use crate::secret_string::{self, SecretString};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// AI Provider types supported by Zest
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -278,6 +282,45 @@ pub struct OAuthFlowResult {
 pub struct OAuthStatusResponse {
     pub status: String,
     pub error: Option<String>,
+    /// Populated by a completed OIDC control-panel login (see `oidc.rs`):
+    /// the `NavigationPage`s the token's groups claim allows. `None` for
+    /// the per-provider OAuth flows, which don't gate navigation.
+    #[serde(default)]
+    pub allowed_pages: Option<Vec<NavigationPage>>,
+}
+
+/// State machine result of `check_oauth_status`, replacing the old generic
+/// status string so the UI can tell a user-rejected consent from a network
+/// failure from an abandoned flow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum OAuthFlowStatus {
+    Pending,
+    Completed,
+    Denied,
+    Canceled,
+    TimedOut,
+    Error { message: String },
+}
+
+/// Result of a client/server version handshake against the proxy's
+/// management API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatibilityStatus {
+    Ok,
+    ClientTooOld,
+    ProxyTooOld,
+}
+
+/// Returned by `check_proxy_compatibility`, so the UI can show a clear
+/// upgrade prompt instead of letting a version mismatch fail deep inside
+/// an unrelated command as an opaque JSON-parse error.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibilityReport {
+    pub proxy_version: String,
+    pub client_version: String,
+    pub status: CompatibilityStatus,
 }
 
 /// Navigation pages
@@ -337,7 +380,7 @@ pub struct AppConfig {
     #[serde(rename = "proxy-url", default)]
     pub proxy_url: String,
     #[serde(rename = "api-keys", default)]
-    pub api_keys: Vec<String>,
+    pub api_keys: Vec<SecretString>,
     #[serde(default)]
     pub debug: bool,
     #[serde(rename = "logging-to-file", default)]
@@ -365,6 +408,42 @@ fn default_true() -> bool { true }
 fn default_retry() -> i32 { 3 }
 fn default_max_retry() -> i32 { 30 }
 
+impl AppConfig {
+    /// Encrypt `remote_management.secret_key` and each entry in `api_keys`
+    /// in place with AES-256-GCM, if `ZEST_MASTER_KEY` is set. A no-op
+    /// (values are left as plaintext) when it isn't, so the feature is
+    /// opt-in and `config.yaml` keeps working unchanged without it.
+    pub fn seal(&mut self) {
+        let Some(key) = secret_string::master_key() else { return };
+
+        if let Ok(sealed) = secret_string::seal_value(self.remote_management.secret_key.expose(), &key) {
+            self.remote_management.secret_key = SecretString::new(sealed);
+        }
+        for api_key in &mut self.api_keys {
+            if let Ok(sealed) = secret_string::seal_value(api_key.expose(), &key) {
+                *api_key = SecretString::new(sealed);
+            }
+        }
+    }
+
+    /// Decrypt any `enc:`-prefixed `remote_management.secret_key`/`api_keys`
+    /// values in place. Values without the prefix are assumed to already be
+    /// plaintext (e.g. written before this feature existed, or written with
+    /// no `ZEST_MASTER_KEY` set) and are left untouched.
+    pub fn unseal(&mut self) {
+        let Some(key) = secret_string::master_key() else { return };
+
+        if let Ok(plain) = secret_string::unseal_value(self.remote_management.secret_key.expose(), &key) {
+            self.remote_management.secret_key = SecretString::new(plain);
+        }
+        for api_key in &mut self.api_keys {
+            if let Ok(plain) = secret_string::unseal_value(api_key.expose(), &key) {
+                *api_key = SecretString::new(plain);
+            }
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -386,13 +465,132 @@ impl Default for AppConfig {
     }
 }
 
+/// Provider-selection strategy for `RoutingConfig::select`. Serializes to the
+/// same kebab-case strings the `strategy` field has always accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoutingStrategy {
+    RoundRobin,
+    Random,
+    LeastRequests,
+    Weighted,
+    QuotaAware,
+}
+
+impl Default for RoutingStrategy {
+    fn default() -> Self {
+        RoutingStrategy::RoundRobin
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RoutingConfig {
-    #[serde(default = "default_strategy")]
-    pub strategy: String,
-}
+    #[serde(default)]
+    pub strategy: RoutingStrategy,
+    /// Only consulted when `strategy` is `Weighted`, keyed by
+    /// `AuthFile::quota_lookup_key()`. An account missing from this map gets
+    /// a default weight of 1.
+    #[serde(default)]
+    pub weights: HashMap<String, u32>,
+}
+
+impl RoutingConfig {
+    /// Pick the `AuthFile` a new request should use, per `self.strategy`.
+    /// Only candidates passing `AuthFile::is_ready()` are considered;
+    /// returns `None` if none qualify.
+    pub fn select<'a>(
+        &self,
+        candidates: &'a [AuthFile],
+        quotas: &HashMap<String, QuotaInfo>,
+    ) -> Option<&'a AuthFile> {
+        let ready: Vec<&AuthFile> = candidates.iter().filter(|c| c.is_ready()).collect();
+        if ready.is_empty() {
+            return None;
+        }
+
+        match self.strategy {
+            RoutingStrategy::RoundRobin => Self::select_round_robin(&ready),
+            RoutingStrategy::Random => Self::select_random(&ready),
+            RoutingStrategy::LeastRequests => Self::select_least_requests(&ready, quotas),
+            RoutingStrategy::Weighted => self.select_weighted(&ready),
+            RoutingStrategy::QuotaAware => self.select_quota_aware(&ready, quotas),
+        }
+    }
+
+    fn select_round_robin<'a>(ready: &[&'a AuthFile]) -> Option<&'a AuthFile> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let index = NEXT.fetch_add(1, Ordering::Relaxed) % ready.len();
+        ready.get(index).copied()
+    }
+
+    fn select_random<'a>(ready: &[&'a AuthFile]) -> Option<&'a AuthFile> {
+        let index = rand::thread_rng().gen_range(0..ready.len());
+        ready.get(index).copied()
+    }
+
+    /// Approximates "least requests" using `QuotaInfo::used` as the running
+    /// request count; accounts with no quota entry yet are treated as 0.
+    fn select_least_requests<'a>(
+        ready: &[&'a AuthFile],
+        quotas: &HashMap<String, QuotaInfo>,
+    ) -> Option<&'a AuthFile> {
+        ready
+            .iter()
+            .copied()
+            .min_by_key(|auth| quotas.get(&auth.quota_lookup_key()).map(|q| q.used).unwrap_or(0))
+    }
+
+    fn select_weighted<'a>(&self, ready: &[&'a AuthFile]) -> Option<&'a AuthFile> {
+        let total_weight: u64 = ready
+            .iter()
+            .map(|auth| *self.weights.get(&auth.quota_lookup_key()).unwrap_or(&1) as u64)
+            .sum();
+        if total_weight == 0 {
+            return ready.first().copied();
+        }
 
-fn default_strategy() -> String { "round-robin".to_string() }
+        let mut pick = rand::thread_rng().gen_range(0..total_weight);
+        for auth in ready {
+            let weight = *self.weights.get(&auth.quota_lookup_key()).unwrap_or(&1) as u64;
+            if pick < weight {
+                return Some(auth);
+            }
+            pick -= weight;
+        }
+        ready.last().copied()
+    }
+
+    /// Selects the ready, non-cooling account with the lowest
+    /// `QuotaInfo::percentage_used()`. Falls back to round-robin if none of
+    /// the eligible candidates have quota info yet.
+    fn select_quota_aware<'a>(
+        &self,
+        ready: &[&'a AuthFile],
+        quotas: &HashMap<String, QuotaInfo>,
+    ) -> Option<&'a AuthFile> {
+        let eligible: Vec<&'a AuthFile> = ready
+            .iter()
+            .copied()
+            .filter(|auth| auth.status != "cooling")
+            .collect();
+        if eligible.is_empty() {
+            return None;
+        }
+
+        let best = eligible
+            .iter()
+            .copied()
+            .filter_map(|auth| {
+                quotas
+                    .get(&auth.quota_lookup_key())
+                    .map(|q| (auth, q.percentage_used()))
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(auth, _)| auth);
+
+        best.or_else(|| Self::select_round_robin(&eligible))
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuotaExceededConfig {
@@ -416,9 +614,48 @@ pub struct RemoteManagementConfig {
     #[serde(rename = "allow-remote", default)]
     pub allow_remote: bool,
     #[serde(rename = "secret-key", default)]
-    pub secret_key: String,
+    pub secret_key: SecretString,
     #[serde(rename = "disable-control-panel", default)]
     pub disable_control_panel: bool,
+    #[serde(rename = "oauth-signature-keys", default)]
+    pub oauth_signature_keys: Option<SignatureKeyPaths>,
+    #[serde(rename = "oidc", default)]
+    pub oidc: Option<OidcConfig>,
+}
+
+/// Paths to the Ed25519 key pair used to sign/verify remote management API
+/// requests (see `http_signature.rs`), so a request carrying the right
+/// `secret_key` can additionally be checked for integrity and authenticity.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SignatureKeyPaths {
+    #[serde(rename = "key-id", default)]
+    pub key_id: String,
+    #[serde(rename = "private-key-path", default)]
+    pub private_key_path: String,
+    #[serde(rename = "public-key-path", default)]
+    pub public_key_path: String,
+}
+
+/// OIDC identity provider used to log into the control panel itself,
+/// separate from the per-`AIProvider` OAuth flows in `commands.rs`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OidcConfig {
+    #[serde(default)]
+    pub issuer: String,
+    #[serde(rename = "client-id", default)]
+    pub client_id: String,
+    #[serde(rename = "client-secret", default)]
+    pub client_secret: SecretString,
+    #[serde(rename = "redirect-uri", default)]
+    pub redirect_uri: String,
+    /// Claim in the ID token holding the user's group memberships, used to
+    /// gate `NavigationPage` access. Defaults to `"groups"`.
+    #[serde(rename = "allowed-groups-claim", default = "default_groups_claim")]
+    pub allowed_groups_claim: String,
+}
+
+fn default_groups_claim() -> String {
+    "groups".to_string()
 }
 
 /// Log entry
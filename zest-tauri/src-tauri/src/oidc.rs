@@ -0,0 +1,226 @@
+//! OIDC login for the control panel itself.
+//!
+//! Distinct from the per-`AIProvider` OAuth flows in `commands.rs`'s
+//! `start_oauth_flow` (which authenticate against an AI provider through the
+//! proxy's `*-auth-url` endpoints), this lets a remote/shared deployment put
+//! SSO in front of the control panel instead of relying on
+//! `RemoteManagementConfig::secret_key` alone. Implements the standard
+//! authorization-code-with-PKCE flow against the provider configured at
+//! `RemoteManagementConfig::oidc`, and gates `NavigationPage` access by the
+//! ID token's groups claim.
+
+use crate::models::{NavigationPage, OAuthFlowResult, OidcConfig};
+use base64::Engine;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum OidcError {
+    #[error("OIDC discovery request failed: {0}")]
+    Discovery(String),
+    #[error("token exchange failed: {0}")]
+    TokenExchange(String),
+    #[error("ID token validation failed: {0}")]
+    InvalidIdToken(String),
+    #[error("unknown or expired login state")]
+    UnknownState,
+}
+
+/// The discovery document fields this flow needs.
+#[derive(Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kid: Option<String>,
+    n: String,
+    e: String,
+}
+
+/// One in-flight login: the PKCE verifier and redirect URI, kept server-side
+/// between [`start_oidc_login`] and [`finish_oidc_login`] the same way
+/// `ProxyStateInner::oauth_flows` tracks per-provider flows.
+struct PendingLogin {
+    code_verifier: String,
+    redirect_uri: String,
+}
+
+static PENDING_LOGINS: Mutex<Option<HashMap<String, PendingLogin>>> = Mutex::new(None);
+
+fn with_pending<R>(f: impl FnOnce(&mut HashMap<String, PendingLogin>) -> R) -> R {
+    let mut guard = PENDING_LOGINS.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(HashMap::new))
+}
+
+async fn fetch_discovery(issuer: &str) -> Result<Discovery, OidcError> {
+    let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+    reqwest::get(&url)
+        .await
+        .map_err(|e| OidcError::Discovery(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| OidcError::Discovery(e.to_string()))
+}
+
+/// Generate a PKCE `(code_verifier, code_challenge)` pair using the `S256`
+/// method, per RFC 7636.
+fn generate_pkce_pair() -> (String, String) {
+    let mut verifier_bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut verifier_bytes);
+    let code_verifier = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(verifier_bytes);
+
+    let mut hasher = Sha256::new();
+    hasher.update(code_verifier.as_bytes());
+    let code_challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(hasher.finalize());
+
+    (code_verifier, code_challenge)
+}
+
+/// Fetch the issuer's discovery document, generate a `state`/PKCE
+/// `code_verifier` pair, stash the verifier keyed by `state`, and return the
+/// authorize URL for the caller to open in a browser.
+pub async fn start_oidc_login(config: &OidcConfig) -> Result<OAuthFlowResult, OidcError> {
+    let discovery = fetch_discovery(&config.issuer).await?;
+
+    let state = uuid::Uuid::new_v4().to_string();
+    let (code_verifier, code_challenge) = generate_pkce_pair();
+
+    let url = format!(
+        "{}?response_type=code&client_id={}&redirect_uri={}&scope=openid%20profile%20email&state={}&code_challenge={}&code_challenge_method=S256",
+        discovery.authorization_endpoint,
+        urlencoding::encode(&config.client_id),
+        urlencoding::encode(&config.redirect_uri),
+        state,
+        code_challenge,
+    );
+
+    with_pending(|pending| {
+        pending.insert(
+            state.clone(),
+            PendingLogin {
+                code_verifier,
+                redirect_uri: config.redirect_uri.clone(),
+            },
+        );
+    });
+
+    Ok(OAuthFlowResult { url, state })
+}
+
+/// Exchange `code` at the token endpoint, validate the returned ID token's
+/// signature and `exp`/`aud`/`iss` claims, and return the `NavigationPage`s
+/// its groups claim allows.
+pub async fn finish_oidc_login(
+    config: &OidcConfig,
+    state: &str,
+    code: &str,
+) -> Result<Vec<NavigationPage>, OidcError> {
+    let pending = with_pending(|pending| pending.remove(state)).ok_or(OidcError::UnknownState)?;
+    let discovery = fetch_discovery(&config.issuer).await?;
+
+    let client = reqwest::Client::new();
+    let token_response: TokenResponse = client
+        .post(&discovery.token_endpoint)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", pending.redirect_uri.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.expose()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| OidcError::TokenExchange(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| OidcError::TokenExchange(e.to_string()))?;
+
+    let claims = validate_id_token(&token_response.id_token, &discovery, config).await?;
+    Ok(allowed_pages(&claims, config))
+}
+
+/// Verify `id_token`'s signature against the issuer's published JWKS, and
+/// its `exp`/`aud`/`iss` claims, returning the decoded claim set.
+async fn validate_id_token(
+    id_token: &str,
+    discovery: &Discovery,
+    config: &OidcConfig,
+) -> Result<serde_json::Value, OidcError> {
+    let header = decode_header(id_token).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+    let kid = header
+        .kid
+        .ok_or_else(|| OidcError::InvalidIdToken("token header has no kid".to_string()))?;
+
+    let jwks: JwkSet = reqwest::get(&discovery.jwks_uri)
+        .await
+        .map_err(|e| OidcError::Discovery(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| OidcError::Discovery(e.to_string()))?;
+
+    let jwk = jwks
+        .keys
+        .iter()
+        .find(|k| k.kid.as_deref() == Some(kid.as_str()))
+        .ok_or_else(|| OidcError::InvalidIdToken("no signing key matches the token's kid".to_string()))?;
+
+    let decoding_key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e)
+        .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.set_audience(&[&config.client_id]);
+    validation.set_issuer(&[&config.issuer]);
+
+    let token_data = decode::<serde_json::Value>(id_token, &decoding_key, &validation)
+        .map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+    Ok(token_data.claims)
+}
+
+/// Map the token's groups claim to the pages it unlocks. A recognized
+/// admin group gets everything; any other non-empty group set gets the
+/// read-only pages; no groups claim at all gets only the minimal
+/// Dashboard/About surface an unauthenticated session would see.
+fn allowed_pages(claims: &serde_json::Value, config: &OidcConfig) -> Vec<NavigationPage> {
+    let groups: Vec<&str> = claims
+        .get(&config.allowed_groups_claim)
+        .and_then(|v| v.as_array())
+        .map(|values| values.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    if groups.is_empty() {
+        return vec![NavigationPage::Dashboard, NavigationPage::About];
+    }
+
+    if groups.iter().any(|g| *g == "admin" || *g == "zest-admins") {
+        return NavigationPage::all();
+    }
+
+    vec![
+        NavigationPage::Dashboard,
+        NavigationPage::Quota,
+        NavigationPage::Providers,
+        NavigationPage::Logs,
+        NavigationPage::About,
+    ]
+}
@@ -0,0 +1,130 @@
+//! 1Password CLI credential backend.
+//!
+//! Drives the `op` CLI so teams can keep Zest's provider API keys in a
+//! shared 1Password vault instead of the local OS keychain. Items are
+//! looked up and created by title, using the same `com.zest.app:<key>`
+//! naming `credentials.rs`'s OS backends use for their target/service name.
+
+use crate::credentials::CredentialError;
+use serde::{Deserialize, Serialize};
+
+/// Which `op` account/vault a request should be scoped to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct OnePasswordConfig {
+    /// `op --account <account>`; left unset to use `op`'s signed-in default
+    #[serde(default)]
+    pub account: Option<String>,
+    /// `op --vault <vault>`; left unset to use `op`'s default vault
+    #[serde(default)]
+    pub vault: Option<String>,
+}
+
+fn item_title(key: &str) -> String {
+    format!("{}:{}", crate::credentials::SERVICE_NAME, key)
+}
+
+/// Resolve the `op` binary the same way a shell's `PATH` lookup would,
+/// rather than assuming it's at a fixed path.
+fn resolve_op_binary() -> Result<std::path::PathBuf, CredentialError> {
+    let exe_name = if cfg!(windows) { "op.exe" } else { "op" };
+
+    std::env::var_os("PATH")
+        .and_then(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join(exe_name))
+                .find(|candidate| candidate.is_file())
+        })
+        .ok_or_else(|| {
+            CredentialError::StoreError(
+                "1Password CLI ('op') not found on PATH; install it from https://developer.1password.com/docs/cli"
+                    .to_string(),
+            )
+        })
+}
+
+fn base_command(config: &OnePasswordConfig) -> Result<std::process::Command, CredentialError> {
+    let mut cmd = std::process::Command::new(resolve_op_binary()?);
+    if let Some(account) = &config.account {
+        cmd.args(["--account", account]);
+    }
+    if let Some(vault) = &config.vault {
+        cmd.args(["--vault", vault]);
+    }
+    Ok(cmd)
+}
+
+/// Map a non-zero `op` exit to a `CredentialError`, recognizing the
+/// "not signed in" case (no active `OP_SESSION`) so the UI can prompt the
+/// user to run `op signin` instead of a generic failure.
+fn map_op_failure(stderr: &str) -> CredentialError {
+    let lowered = stderr.to_lowercase();
+    if lowered.contains("not currently signed in") || lowered.contains("you are not signed in") {
+        CredentialError::NotSignedIn
+    } else if lowered.contains("isn't an item") || lowered.contains("no item found") {
+        CredentialError::NotFound
+    } else {
+        CredentialError::RetrieveError(stderr.trim().to_string())
+    }
+}
+
+pub fn get(config: &OnePasswordConfig, key: &str) -> Result<String, CredentialError> {
+    let output = base_command(config)?
+        .args(["item", "get", &item_title(key), "--fields", "label=password", "--reveal"])
+        .output()
+        .map_err(|e| CredentialError::RetrieveError(e.to_string()))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(map_op_failure(&String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+pub fn store(config: &OnePasswordConfig, key: &str, value: &str) -> Result<(), CredentialError> {
+    let title = item_title(key);
+
+    // `op item edit` fails if the item doesn't exist yet, so probe first
+    // and fall back to `item create` rather than parsing its error text.
+    let exists = base_command(config)?
+        .args(["item", "get", &title])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+
+    let mut cmd = base_command(config)?;
+    if exists {
+        cmd.args(["item", "edit", &title, &format!("password={value}")]);
+    } else {
+        cmd.args([
+            "item",
+            "create",
+            "--category",
+            "password",
+            "--title",
+            &title,
+            &format!("password={value}"),
+        ]);
+    }
+
+    let output = cmd.output().map_err(|e| CredentialError::StoreError(e.to_string()))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(map_op_failure(&String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+pub fn erase(config: &OnePasswordConfig, key: &str) -> Result<(), CredentialError> {
+    let output = base_command(config)?
+        .args(["item", "delete", &item_title(key)])
+        .output()
+        .map_err(|e| CredentialError::DeleteError(e.to_string()))?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => match map_op_failure(&String::from_utf8_lossy(&output.stderr)) {
+            CredentialError::NotFound => Ok(()),
+            other => Err(other),
+        },
+    }
+}
@@ -0,0 +1,168 @@
+//! Centralized filesystem locations for agent configs, shim scripts, and
+//! binary discovery search directories.
+//!
+//! These used to be hardcoded and duplicated across `configure_agent_json`,
+//! `get_agent_backups`, and `restore_agent_backup` — the `~/.claude` vs
+//! `~/.config/claude` mismatch fixed in those functions' comments shows
+//! how easily they drifted. Every accessor here is computed once (cached
+//! in a `OnceLock`) from `$HOME`/XDG vars, and honors a `ZEST_CONFIG_HOME`
+//! (falling back to `XDG_CONFIG_HOME`) override so tests and power users
+//! can redirect the whole agent-config surface to a sandbox directory
+//! without touching real dotfiles. Binary *discovery* search dirs are
+//! deliberately not affected by that override — they describe where real
+//! tool installs live on the host, not where Zest stores its own config.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// The root agent configs are resolved against: `$ZEST_CONFIG_HOME`, else
+/// `$XDG_CONFIG_HOME`, else the real `$HOME`. Every per-agent path below
+/// is `config_root().join(<the same relative path it'd have under $HOME>)`,
+/// so overriding this redirects `~/.claude`, `~/.codex`, `~/.config/amp`,
+/// etc. all at once.
+fn config_root() -> &'static PathBuf {
+    static CONFIG_ROOT: OnceLock<PathBuf> = OnceLock::new();
+    CONFIG_ROOT.get_or_init(|| {
+        std::env::var("ZEST_CONFIG_HOME")
+            .or_else(|_| std::env::var("XDG_CONFIG_HOME"))
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home_dir())
+    })
+}
+
+pub fn claude_config_dir() -> PathBuf {
+    config_root().join(".claude")
+}
+
+pub fn claude_settings_path() -> PathBuf {
+    claude_config_dir().join("settings.json")
+}
+
+pub fn codex_config_dir() -> PathBuf {
+    config_root().join(".codex")
+}
+
+pub fn codex_config_path() -> PathBuf {
+    codex_config_dir().join("config.toml")
+}
+
+pub fn opencode_config_dir() -> PathBuf {
+    config_root().join(".config").join("opencode")
+}
+
+pub fn opencode_config_path() -> PathBuf {
+    opencode_config_dir().join("opencode.json")
+}
+
+pub fn amp_config_dir() -> PathBuf {
+    config_root().join(".config").join("amp")
+}
+
+pub fn amp_config_path() -> PathBuf {
+    amp_config_dir().join("settings.json")
+}
+
+pub fn factory_droid_config_dir() -> PathBuf {
+    config_root().join(".factory")
+}
+
+pub fn factory_droid_config_path() -> PathBuf {
+    factory_droid_config_dir().join("config.json")
+}
+
+/// The config directory `get_agent_backups` should scan for `agent_id`,
+/// if it has one.
+pub fn agent_config_dir(agent_id: &str) -> Option<PathBuf> {
+    match agent_id {
+        "claude-code" => Some(claude_config_dir()),
+        "codex" => Some(codex_config_dir()),
+        "opencode" => Some(opencode_config_dir()),
+        "amp" => Some(amp_config_dir()),
+        "factory-droid" => Some(factory_droid_config_dir()),
+        _ => None,
+    }
+}
+
+/// The config file `configure_agent_json`/`restore_agent_backup` should
+/// act on for `agent_id`, if it has one.
+pub fn agent_config_path(agent_id: &str) -> Option<PathBuf> {
+    match agent_id {
+        "claude-code" => Some(claude_settings_path()),
+        "codex" => Some(codex_config_path()),
+        "opencode" => Some(opencode_config_path()),
+        "amp" => Some(amp_config_path()),
+        "factory-droid" => Some(factory_droid_config_path()),
+        _ => None,
+    }
+}
+
+/// Directory holding Zest-managed shim scripts (see `crate::shims`).
+pub fn shim_dir() -> PathBuf {
+    config_root().join(".zest").join("shims")
+}
+
+/// File the running GUI writes its one-shot IPC server name to on startup,
+/// and the `zest` CLI binary reads to connect to it (see `crate::ipc`).
+pub fn ipc_server_file() -> PathBuf {
+    config_root().join(".zest").join("ipc-server")
+}
+
+/// Hardcoded common CLI tool installation paths (ordered by priority), for
+/// `find_agent_binary` to merge with the recovered login-shell `PATH` via
+/// `environment::normalize_pathlist`. Always resolved against the real
+/// `$HOME`, not `config_root()` — these describe where tools actually
+/// install, independent of where Zest stores its own config.
+pub fn discovery_dirs() -> Vec<PathBuf> {
+    let home = home_dir();
+
+    vec![
+        // macOS Homebrew paths
+        PathBuf::from("/opt/homebrew/bin"), // Apple Silicon
+        PathBuf::from("/usr/local/bin"),    // Intel Mac / Linux
+        // System paths
+        PathBuf::from("/usr/bin"),
+        PathBuf::from("/bin"),
+        // User local paths
+        home.join(".local/bin"),
+        // Package manager paths
+        home.join(".cargo/bin"),        // Rust/Cargo
+        home.join(".bun/bin"),          // Bun
+        home.join(".deno/bin"),         // Deno
+        home.join(".npm-global/bin"),   // npm global
+        home.join("node_modules/.bin"), // Local npm
+        // Tool-specific paths
+        home.join(".opencode/bin"),
+        home.join(".warp/bin"),
+        home.join(".claude/bin"),
+        home.join(".amp/bin"),
+        // Version manager shims (static paths)
+        home.join(".volta/bin"),              // Volta
+        home.join(".asdf/shims"),              // asdf
+        home.join(".local/share/mise/shims"), // mise (modern asdf alternative)
+        home.join(".mise/shims"),             // mise alternative path
+        // pnpm
+        home.join(".pnpm"),
+        home.join("Library/pnpm"), // macOS pnpm
+        // Yarn
+        home.join(".yarn/bin"),
+        // Go
+        home.join("go/bin"),
+        home.join(".go/bin"),
+        // Additional common paths
+        PathBuf::from("/opt/local/bin"), // MacPorts
+        PathBuf::from("/snap/bin"),      // Snap (Linux)
+        // Windows-specific paths
+        #[cfg(windows)]
+        home.join("AppData/Local/Programs"),
+        #[cfg(windows)]
+        home.join("AppData/Roaming/npm"),
+        #[cfg(windows)]
+        PathBuf::from("C:/Program Files/nodejs"),
+        #[cfg(windows)]
+        PathBuf::from("C:/ProgramData/chocolatey/bin"),
+    ]
+}
@@ -0,0 +1,199 @@
+//! Scoped, role-based authorization for provider API keys.
+//!
+//! Every key stored through `add_api_key` carries a [`KeyScope`] describing
+//! what it's allowed to do. At load time each scope is turned into a chain
+//! of [`Policy`] objects; `check_key_authorized` evaluates that chain before
+//! a request is allowed through.
+//!
+//! Note: the proxy itself is an external managed binary, not an in-process
+//! request router, so `rate_limit_per_minute` is tracked as scope metadata
+//! the frontend can surface, not a live request throttle.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Authorization scope attached to a stored API key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyScope {
+    /// Providers this key may call. Empty means "all providers".
+    #[serde(default)]
+    pub providers: Vec<String>,
+    /// Glob patterns (e.g. `"gpt-4*"`) for models this key may call. Empty
+    /// means "all models".
+    #[serde(default)]
+    pub model_globs: Vec<String>,
+    /// RFC 3339 timestamp after which the key is no longer valid.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Advisory rate cap surfaced to the frontend; not independently
+    /// enforced (see module docs).
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+impl KeyScope {
+    /// A scope with no restrictions: all providers, all models, no expiry.
+    pub fn unrestricted() -> Self {
+        Self::default()
+    }
+
+    /// Whether this scope's expiry (if any) is in the past. A timestamp
+    /// that fails to parse is treated as not expired rather than rejected
+    /// outright.
+    pub fn is_expired(&self) -> bool {
+        match &self.expires_at {
+            Some(ts) => chrono::DateTime::parse_from_rfc3339(ts)
+                .map(|expiry| expiry < chrono::Utc::now())
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+/// A stored API key plus its scope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub key: String,
+    #[serde(default)]
+    pub scope: KeyScope,
+    pub created_at: String,
+}
+
+/// A request being checked against a key's policies.
+pub struct RequestContext<'a> {
+    pub key: &'a str,
+    pub provider: &'a str,
+    pub model: &'a str,
+}
+
+/// A single authorization rule a key's scope is turned into.
+pub trait Policy {
+    fn authorize(&self, req: &RequestContext) -> bool;
+}
+
+/// No restrictions: always authorizes.
+pub struct AdminPolicy;
+
+impl Policy for AdminPolicy {
+    fn authorize(&self, _req: &RequestContext) -> bool {
+        true
+    }
+}
+
+/// Authorizes only requests to one of a fixed set of providers.
+pub struct ProviderScopedPolicy {
+    pub providers: Vec<String>,
+}
+
+impl Policy for ProviderScopedPolicy {
+    fn authorize(&self, req: &RequestContext) -> bool {
+        self.providers.iter().any(|p| p.eq_ignore_ascii_case(req.provider))
+    }
+}
+
+/// Authorizes only requests to a model matching one of a set of globs
+/// (`*` wildcard only, matched case-sensitively).
+pub struct ModelScopedPolicy {
+    pub model_globs: Vec<String>,
+}
+
+impl Policy for ModelScopedPolicy {
+    fn authorize(&self, req: &RequestContext) -> bool {
+        self.model_globs.iter().any(|glob| glob_matches(glob, req.model))
+    }
+}
+
+fn glob_matches(glob: &str, value: &str) -> bool {
+    match glob.split_once('*') {
+        None => glob == value,
+        Some((prefix, suffix)) => value.starts_with(prefix) && value.ends_with(suffix),
+    }
+}
+
+/// Build the policy chain for a key's scope. All policies in the chain must
+/// authorize for the request to be allowed.
+fn policies_for(scope: &KeyScope) -> Vec<Box<dyn Policy>> {
+    let mut policies: Vec<Box<dyn Policy>> = Vec::new();
+
+    if !scope.providers.is_empty() {
+        policies.push(Box::new(ProviderScopedPolicy {
+            providers: scope.providers.clone(),
+        }));
+    }
+
+    if !scope.model_globs.is_empty() {
+        policies.push(Box::new(ModelScopedPolicy {
+            model_globs: scope.model_globs.clone(),
+        }));
+    }
+
+    if policies.is_empty() {
+        policies.push(Box::new(AdminPolicy));
+    }
+
+    policies
+}
+
+fn registry_path() -> PathBuf {
+    crate::proxy::ProxyStateInner::data_dir().join("api_key_scopes.json")
+}
+
+/// Load all stored key scopes, including expired ones — callers that care
+/// about expiry (`is_authorized`, `get_api_keys`) check it themselves, since
+/// filtering here would make expiry unenforceable for anyone reading the
+/// registry after the fact.
+pub fn load_registry() -> Result<Vec<ApiKeyRecord>, String> {
+    let path = registry_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn save_registry(records: &[ApiKeyRecord]) -> Result<(), String> {
+    let path = registry_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(records).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// Record (or replace) the scope for a key.
+pub fn upsert_scope(key: &str, scope: KeyScope) -> Result<(), String> {
+    let mut records = load_registry()?;
+    records.retain(|r| r.key != key);
+    records.push(ApiKeyRecord {
+        key: key.to_string(),
+        scope,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    });
+    save_registry(&records)
+}
+
+/// Remove a key's stored scope (called alongside `delete_api_key`).
+pub fn remove_scope(key: &str) -> Result<(), String> {
+    let mut records = load_registry()?;
+    records.retain(|r| r.key != key);
+    save_registry(&records)
+}
+
+/// Evaluate a key's policy chain against a provider/model pair. A key with
+/// no stored scope record (e.g. one added before this feature existed) is
+/// treated as unrestricted, matching prior behavior.
+pub fn is_authorized(key: &str, provider: &str, model: &str) -> Result<bool, String> {
+    let records = load_registry()?;
+    let Some(record) = records.iter().find(|r| r.key == key) else {
+        return Ok(true);
+    };
+
+    if record.scope.is_expired() {
+        return Ok(false);
+    }
+
+    let req = RequestContext { key, provider, model };
+    Ok(policies_for(&record.scope).iter().all(|p| p.authorize(&req)))
+}
@@ -4,17 +4,19 @@
 //! This is a port of CLIProxyManager.swift to Rust.
 
 use crate::models::{AuthFile, AuthFilesResponse, ProxyStatus, ApiKeysResponse};
+use metrics::{counter, gauge, histogram};
 use serde::Serialize;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 use thiserror::Error;
 
 /// GitHub repository for CLIProxyAPI releases
-const GITHUB_REPO: &str = "router-for-me/CLIProxyAPIPlus";
+pub(crate) const GITHUB_REPO: &str = "router-for-me/CLIProxyAPIPlus";
 const BINARY_NAME: &str = "CLIProxyAPI";
 
 #[derive(Error, Debug)]
@@ -31,6 +33,10 @@ pub enum ProxyError {
     ExtractionFailed(String),
     #[error("Download failed: {0}")]
     DownloadFailed(String),
+    #[error("Checksum mismatch for {asset}: expected {expected}, got {found}")]
+    ChecksumMismatch { asset: String, expected: String, found: String },
+    #[error("Installed proxy version {found} is older than the minimum supported version {required}; reinstall to upgrade")]
+    IncompatibleVersion { found: String, required: String },
     #[error("Config error: {0}")]
     ConfigError(String),
     #[error("Process error: {0}")]
@@ -49,6 +55,7 @@ impl Serialize for ProxyError {
 }
 
 /// Shared proxy state managed by Tauri
+#[derive(Clone)]
 pub struct ProxyState {
     pub inner: Arc<Mutex<ProxyStateInner>>,
 }
@@ -75,6 +82,151 @@ pub struct ProxyStateInner {
     pub is_downloading: bool,
     pub download_progress: f64,
     pub last_error: Option<String>,
+    /// Outstanding OAuth flows keyed by `oauth_state`, so concurrent logins
+    /// (e.g. Gemini + Claude) don't clobber each other.
+    pub oauth_flows: std::collections::HashMap<String, OAuthFlow>,
+    /// Bounded ring buffer of the managed process's stdout/stderr, so a
+    /// crash or startup failure can be explained instead of silently
+    /// discarded. Cleared on every `start_proxy`.
+    pub log_lines: std::collections::VecDeque<ProxyLogLine>,
+    /// When the currently running process was started, used to compute
+    /// `status.uptime_seconds`. `None` while stopped.
+    pub started_at: Option<std::time::Instant>,
+    /// Set by `stop_proxy` right before it kills the process, so the
+    /// health supervisor can tell a requested stop apart from a crash and
+    /// skip auto-restarting in the former case.
+    pub intentional_stop: bool,
+    /// Whether a health-supervisor task is already polling this instance,
+    /// so `start_proxy` doesn't spawn a second one on every restart.
+    pub supervising: bool,
+    /// Outbound proxy last resolved by `start_proxy`, re-read by the
+    /// supervisor on each auto-restart attempt.
+    pub outbound_proxy: Option<String>,
+    /// Custom CA bundle (see `AppSettings::ca_bundle_path`) last resolved by
+    /// `start_proxy`, used to build the HTTP clients `fetch_auth_files`/
+    /// `fetch_api_keys`/`check_health` talk to the management API with.
+    pub ca_bundle_path: Option<PathBuf>,
+    /// When the supervisor's periodic `/meta` probe last succeeded. `None`
+    /// until the first probe completes.
+    pub last_health_success: Option<std::time::Instant>,
+    /// How many `/meta` probes in a row have failed. Reset to 0 on the
+    /// first success; once it reaches `HEALTH_FAILURE_THRESHOLD` the
+    /// supervisor treats the instance as unhealthy and restarts it.
+    pub consecutive_health_failures: u32,
+}
+
+/// Most log lines recall in `log_lines` / replay over `get_proxy_logs`
+/// before the oldest ones are dropped.
+const LOG_BUFFER_CAPACITY: usize = 500;
+
+/// Which pipe a captured log line came from.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line captured from the managed process, emitted live as a
+/// `proxy-log` event and retained in `ProxyStateInner::log_lines`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyLogLine {
+    pub stream: LogStream,
+    /// Best-effort level parsed from the line (e.g. `"ERROR"`, `"WARN"`),
+    /// `None` when the line doesn't look like a leveled log entry.
+    pub level: Option<String>,
+    pub message: String,
+}
+
+/// Heuristically pull a log level out of a line such as
+/// `2024-01-01T00:00:00Z [ERROR] failed to bind port` or
+/// `time="..." level=warn msg="..."`, by looking for a standalone,
+/// case-insensitive level token.
+fn parse_log_level(line: &str) -> Option<String> {
+    const LEVELS: [&str; 5] = ["ERROR", "WARN", "WARNING", "INFO", "DEBUG"];
+    let upper = line.to_uppercase();
+    let tokens: Vec<&str> = upper.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+    for level in LEVELS {
+        if tokens.contains(&level) {
+            return Some(if level == "WARNING" { "WARN".to_string() } else { level.to_string() });
+        }
+    }
+    None
+}
+
+/// Record one captured process line: append it to the bounded buffer and
+/// emit it as a `proxy-log` event for the frontend to stream live.
+async fn record_log_line<R: tauri::Runtime>(state: &Arc<Mutex<ProxyStateInner>>, app: &AppHandle<R>, stream: LogStream, message: String) {
+    let entry = ProxyLogLine {
+        stream,
+        level: parse_log_level(&message),
+        message,
+    };
+
+    {
+        let mut inner = state.lock().await;
+        inner.log_lines.push_back(entry.clone());
+        while inner.log_lines.len() > LOG_BUFFER_CAPACITY {
+            inner.log_lines.pop_front();
+        }
+    }
+
+    let _ = app.emit("proxy-log", entry);
+}
+
+/// Build a `StartupFailed` detail message including the last few captured
+/// stderr lines, so a crash-on-launch surfaces its actual reason instead of
+/// just an exit status.
+fn startup_failure_detail(inner: &ProxyStateInner, status: std::process::ExitStatus) -> String {
+    let tail: Vec<&str> = inner
+        .log_lines
+        .iter()
+        .rev()
+        .filter(|l| matches!(l.stream, LogStream::Stderr))
+        .take(5)
+        .map(|l| l.message.as_str())
+        .collect();
+
+    if tail.is_empty() {
+        format!("Process exited immediately with status: {:?}", status)
+    } else {
+        let tail: Vec<&str> = tail.into_iter().rev().collect();
+        format!("Process exited immediately with status: {:?}\n{}", status, tail.join("\n"))
+    }
+}
+
+/// Snapshot of the managed process's captured stdout/stderr, oldest first.
+pub async fn get_proxy_logs(state: &Arc<Mutex<ProxyStateInner>>) -> Vec<ProxyLogLine> {
+    state.lock().await.log_lines.iter().cloned().collect()
+}
+
+/// An outstanding OAuth flow tracked between `start_oauth_flow` and its
+/// resolution (success, denial, cancellation, or timeout).
+pub struct OAuthFlow {
+    pub provider: String,
+    pub started_at: std::time::Instant,
+    pub canceled: bool,
+}
+
+/// How long an OAuth flow may remain unresolved before `check_oauth_status`
+/// reports it as `TimedOut` and drops it.
+pub const OAUTH_FLOW_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// The app's own version, sent as `X-Zest-Version` on every management
+/// request made through `ManagementApiClient` (the direct requests in
+/// `fetch_auth_files`/`fetch_api_keys` don't carry it) and compared against the proxy's advertised minimum client
+/// version in `check_proxy_compatibility`.
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Oldest proxy version this app release knows how to talk to.
+pub const MIN_SUPPORTED_PROXY_VERSION: &str = "1.0.0";
+
+/// Response shape of the proxy's `/version` management endpoint.
+#[derive(serde::Deserialize)]
+pub struct ProxyVersionInfo {
+    pub version: String,
+    #[serde(default)]
+    pub min_client_version: Option<String>,
 }
 
 impl ProxyStateInner {
@@ -112,6 +264,15 @@ impl ProxyStateInner {
             is_downloading: false,
             download_progress: 0.0,
             last_error: None,
+            oauth_flows: std::collections::HashMap::new(),
+            log_lines: std::collections::VecDeque::new(),
+            started_at: None,
+            intentional_stop: false,
+            supervising: false,
+            outbound_proxy: None,
+            ca_bundle_path: None,
+            last_health_success: None,
+            consecutive_health_failures: 0,
         }
     }
 
@@ -277,8 +438,11 @@ impl ProxyStateInner {
         format!("{}/v0/management", self.base_url())
     }
 
-    /// Ensure config file exists with default values
-    pub fn ensure_config_exists(&self) -> Result<(), ProxyError> {
+    /// Ensure config file exists with default values. `outbound_proxy` is
+    /// written into the `proxy-url` field so the managed binary routes its
+    /// own upstream requests (to AI providers) through the same proxy Zest
+    /// itself uses for GitHub/asset downloads.
+    pub fn ensure_config_exists(&self, outbound_proxy: Option<&str>) -> Result<(), ProxyError> {
         let config_path = Self::config_path();
 
         // Create data directory if it doesn't exist
@@ -296,7 +460,7 @@ impl ProxyStateInner {
                 r#"host: "127.0.0.1"
 port: {}
 auth-dir: "{}"
-proxy-url: ""
+proxy-url: "{}"
 
 api-keys:
   - "zest-local-{}"
@@ -321,6 +485,7 @@ max-retry-interval: 30
 "#,
                 self.status.port,
                 Self::auth_dir().display(),
+                outbound_proxy.unwrap_or(""),
                 uuid::Uuid::new_v4().to_string().split('-').next().unwrap_or(""),
                 self.management_key
             );
@@ -377,8 +542,18 @@ impl Default for ProxyStateInner {
     }
 }
 
-/// Start the proxy server
-pub async fn start_proxy(state: &Arc<Mutex<ProxyStateInner>>) -> Result<ProxyStatus, ProxyError> {
+/// Start the proxy server. `outbound_proxy` (see `resolve_outbound_proxy`)
+/// is written into the generated config's `proxy-url` field so the managed
+/// binary's own upstream requests go through it. `ca_bundle_path` (see
+/// `AppSettings::ca_bundle_path`) is remembered so `fetch_auth_files`/
+/// `fetch_api_keys`/`check_health` can trust a corporate TLS-inspecting
+/// gateway's certificate when they talk to the management API.
+pub async fn start_proxy<R: tauri::Runtime>(
+    app: &AppHandle<R>,
+    state: &Arc<Mutex<ProxyStateInner>>,
+    outbound_proxy: Option<&str>,
+    ca_bundle_path: Option<&std::path::Path>,
+) -> Result<ProxyStatus, ProxyError> {
     let mut inner = state.lock().await;
 
     if !ProxyStateInner::is_binary_installed() {
@@ -391,9 +566,12 @@ pub async fn start_proxy(state: &Arc<Mutex<ProxyStateInner>>) -> Result<ProxySta
 
     inner.is_starting = true;
     inner.last_error = None;
+    inner.log_lines.clear();
+    inner.outbound_proxy = outbound_proxy.map(|s| s.to_string());
+    inner.ca_bundle_path = ca_bundle_path.map(|p| p.to_path_buf());
 
     // Ensure config exists
-    inner.ensure_config_exists()?;
+    inner.ensure_config_exists(outbound_proxy)?;
     inner.sync_secret_key_in_config()?;
 
     let binary_path = ProxyStateInner::binary_path();
@@ -419,36 +597,45 @@ pub async fn start_proxy(state: &Arc<Mutex<ProxyStateInner>>) -> Result<ProxySta
 
     let pid = child.id();
 
-    // Drain stdout/stderr to prevent buffer deadlock
+    // Stream stdout/stderr into the bounded log buffer and `proxy-log`
+    // events instead of just draining them to prevent buffer deadlock.
     if let Some(stdout) = child.stdout.take() {
+        let state = state.clone();
+        let app = app.clone();
         tokio::spawn(async move {
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
-            while let Ok(Some(_line)) = lines.next_line().await {
-                // Discard output to prevent buffer filling
+            while let Ok(Some(line)) = lines.next_line().await {
+                record_log_line(&state, &app, LogStream::Stdout, line).await;
             }
         });
     }
 
     if let Some(stderr) = child.stderr.take() {
+        let state = state.clone();
+        let app = app.clone();
         tokio::spawn(async move {
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
-            while let Ok(Some(_line)) = lines.next_line().await {
-                // Discard errors to prevent buffer filling
+            while let Ok(Some(line)) = lines.next_line().await {
+                record_log_line(&state, &app, LogStream::Stderr, line).await;
             }
         });
     }
 
-    // Wait for startup
+    // Drop the lock while we wait for startup so the drain tasks above can
+    // actually record lines into the shared buffer during that window.
+    drop(inner);
     tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+    let mut inner = state.lock().await;
 
     // Check if process is still running
     match child.try_wait() {
         Ok(Some(status)) => {
             inner.is_starting = false;
-            inner.last_error = Some(format!("Process exited with status: {:?}", status));
-            return Err(ProxyError::StartupFailed(format!("Process exited immediately with status: {:?}", status)));
+            let detail = startup_failure_detail(&inner, status);
+            inner.last_error = Some(detail.clone());
+            return Err(ProxyError::StartupFailed(detail));
         }
         Err(e) => {
             inner.is_starting = false;
@@ -478,32 +665,277 @@ pub async fn start_proxy(state: &Arc<Mutex<ProxyStateInner>>) -> Result<ProxySta
                inner.status.port,
                if inner.management_key.len() > 8 { &inner.management_key[..8] } else { &inner.management_key });
 
-    Ok(inner.status.clone())
+    // Query the freshly started proxy's advertised version so `status.version`
+    // reflects reality, and refuse to keep a binary running that's older than
+    // this app release knows how to talk to, rather than letting the
+    // mismatch surface later as an opaque failure inside `fetch_logs`/
+    // `fetch_usage`.
+    let client = crate::management_client::ManagementApiClient::new(&inner);
+    drop(inner);
+
+    match client.version().await {
+        Ok(info) => {
+            if crate::commands::version_compare(&info.version, MIN_SUPPORTED_PROXY_VERSION) == std::cmp::Ordering::Less {
+                let mut inner = state.lock().await;
+                if let Some(mut process) = inner.process.take() {
+                    let _ = process.kill().await;
+                }
+                inner.status.running = false;
+                inner.status.pid = None;
+                let err = ProxyError::IncompatibleVersion {
+                    found: info.version.clone(),
+                    required: MIN_SUPPORTED_PROXY_VERSION.to_string(),
+                };
+                inner.last_error = Some(err.to_string());
+                return Err(err);
+            }
+
+            let mut inner = state.lock().await;
+            inner.status.version = Some(info.version);
+            begin_supervision(&mut inner, app, state);
+            Ok(inner.status.clone())
+        }
+        Err(e) => {
+            // An older proxy release may not expose `/version` at all;
+            // don't fail startup over that, just leave `status.version` unset.
+            log::debug!("Failed to query proxy version after startup: {}", e);
+            let mut inner = state.lock().await;
+            begin_supervision(&mut inner, app, state);
+            Ok(inner.status.clone())
+        }
+    }
+}
+
+/// Mark the process as started (for uptime tracking) and, unless one is
+/// already running, spawn the health-supervisor task that watches for an
+/// unexpected exit and auto-restarts with backoff.
+fn begin_supervision<R: tauri::Runtime>(inner: &mut ProxyStateInner, app: &AppHandle<R>, state: &Arc<Mutex<ProxyStateInner>>) {
+    inner.started_at = Some(std::time::Instant::now());
+    inner.intentional_stop = false;
+    if !inner.supervising {
+        inner.supervising = true;
+        spawn_supervisor(app.clone(), state.clone());
+    }
+}
+
+const SUPERVISOR_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const SUPERVISOR_INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(2);
+const SUPERVISOR_MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(60);
+const SUPERVISOR_MAX_RESTARTS: u32 = 5;
+/// Consecutive failed `/meta` probes before a still-running-but-unresponsive
+/// process is considered unhealthy and killed for restart, same as an
+/// actual process exit.
+const HEALTH_FAILURE_THRESHOLD: u32 = 3;
+const HEALTH_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+#[derive(Debug, Clone, Serialize)]
+struct ProxyHealthChanged {
+    healthy: bool,
+}
+
+/// Send a single `GET {base_url}/meta` with a short timeout, using the same
+/// proxy/CA settings as the management-API calls. Called with no lock held,
+/// since this is a network round-trip on the supervisor's own poll cadence.
+async fn probe_health(base_url: &str, outbound_proxy: Option<String>, ca_bundle_path: Option<PathBuf>) -> bool {
+    let client = match build_http_client(&HttpClientConfig { outbound_proxy, ca_bundle_path, max_redirects: None }) {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("failed to build health-check client: {}", e);
+            return false;
+        }
+    };
+    client
+        .get(format!("{}/meta", base_url))
+        .timeout(HEALTH_CHECK_TIMEOUT)
+        .send()
+        .await
+        .is_ok_and(|r| r.status().is_success())
+}
+
+/// Update `last_health_success`/`consecutive_health_failures` from a probe
+/// result and emit `proxy-health-changed` on a healthy/unhealthy transition.
+fn record_health_result<R: tauri::Runtime>(app: &AppHandle<R>, inner: &mut ProxyStateInner, healthy: bool) {
+    let was_unhealthy = inner.consecutive_health_failures >= HEALTH_FAILURE_THRESHOLD;
+
+    if healthy {
+        inner.last_health_success = Some(std::time::Instant::now());
+        inner.consecutive_health_failures = 0;
+    } else {
+        inner.consecutive_health_failures += 1;
+    }
+
+    let is_unhealthy = inner.consecutive_health_failures >= HEALTH_FAILURE_THRESHOLD;
+    gauge!("zest_proxy_healthy").set(if is_unhealthy { 0.0 } else { 1.0 });
+    if is_unhealthy != was_unhealthy {
+        let _ = app.emit("proxy-health-changed", ProxyHealthChanged { healthy: !is_unhealthy });
+    }
+}
+
+/// Poll the managed process for an unexpected exit or unresponsiveness
+/// (via a periodic `/meta` probe), track uptime, and restart it with
+/// exponential backoff (giving up after `SUPERVISOR_MAX_RESTARTS`
+/// consecutive failures) unless the exit was caused by an intentional
+/// `stop_proxy`. Exactly one supervisor runs per proxy instance at a time,
+/// guarded by `ProxyStateInner::supervising`; a successful auto-restart
+/// hands the same loop a fresh child to watch rather than spawning another
+/// one.
+fn spawn_supervisor<R: tauri::Runtime>(app: AppHandle<R>, state: Arc<Mutex<ProxyStateInner>>) {
+    tokio::spawn(async move {
+        let mut backoff = SUPERVISOR_INITIAL_BACKOFF;
+        let mut consecutive_restarts = 0u32;
+
+        loop {
+            tokio::time::sleep(SUPERVISOR_POLL_INTERVAL).await;
+            let mut inner = state.lock().await;
+
+            if !inner.status.running {
+                inner.supervising = false;
+                return;
+            }
+
+            if let Some(started_at) = inner.started_at {
+                inner.status.uptime_seconds = Some(started_at.elapsed().as_secs());
+            }
+
+            let exit_status = match inner.process.as_mut() {
+                Some(child) => child.try_wait().ok().flatten(),
+                None => None,
+            };
+
+            let unhealthy = match exit_status {
+                Some(status) => {
+                    log::warn!("Proxy process exited unexpectedly ({:?})", status);
+                    true
+                }
+                None => {
+                    let base_url = inner.base_url();
+                    let outbound_proxy = inner.outbound_proxy.clone();
+                    let ca_bundle_path = inner.ca_bundle_path.clone();
+                    drop(inner);
+                    let healthy = probe_health(&base_url, outbound_proxy, ca_bundle_path).await;
+                    inner = state.lock().await;
+                    record_health_result(&app, &mut inner, healthy);
+                    inner.consecutive_health_failures >= HEALTH_FAILURE_THRESHOLD
+                }
+            };
+
+            if !unhealthy {
+                continue;
+            }
+
+            if let Some(mut process) = inner.process.take() {
+                // Still running but failing its health probe - kill it so
+                // the restart below starts from a clean slate.
+                let _ = process.kill().await;
+            }
+
+            let was_intentional = inner.intentional_stop;
+            inner.status.running = false;
+            inner.status.pid = None;
+            inner.process = None;
+            inner.consecutive_health_failures = 0;
+
+            if was_intentional {
+                inner.supervising = false;
+                return;
+            }
+
+            let outbound_proxy = inner.outbound_proxy.clone();
+            let ca_bundle_path = inner.ca_bundle_path.clone();
+            drop(inner);
+
+            loop {
+                if consecutive_restarts >= SUPERVISOR_MAX_RESTARTS {
+                    log::error!("Proxy crashed {} times in a row; giving up auto-restart", consecutive_restarts);
+                    state.lock().await.supervising = false;
+                    return;
+                }
+                consecutive_restarts += 1;
+                log::warn!("Attempting to auto-restart the proxy (attempt {})", consecutive_restarts);
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(SUPERVISOR_MAX_BACKOFF);
+
+                match start_proxy(&app, &state, outbound_proxy.as_deref(), ca_bundle_path.as_deref()).await {
+                    Ok(_) => {
+                        consecutive_restarts = 0;
+                        break;
+                    }
+                    Err(e) => log::error!("Auto-restart of proxy failed: {}", e),
+                }
+            }
+        }
+    });
+}
+
+/// How long to wait for the proxy to exit on its own after a graceful stop
+/// request before escalating to a forced kill.
+const GRACEFUL_SHUTDOWN_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(5);
+
+/// Ask `pid` to shut down gracefully. On Unix this is a `SIGTERM`, which the
+/// CLIProxyAPI binary can trap to flush state before exiting; on Windows we
+/// have no equivalent signal, so we fall back to a plain (non-forceful)
+/// `taskkill`, escalating to `taskkill /F` alongside the rest of the process
+/// on a timeout, just like the Unix `SIGKILL` fallback below.
+#[cfg(unix)]
+fn request_graceful_exit(pid: u32) {
+    unsafe {
+        libc::kill(pid as i32, libc::SIGTERM);
+    }
+}
+
+#[cfg(windows)]
+fn request_graceful_exit(pid: u32) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .output();
 }
 
 /// Stop the proxy server
 pub async fn stop_proxy(state: &Arc<Mutex<ProxyStateInner>>) -> Result<ProxyStatus, ProxyError> {
     let mut inner = state.lock().await;
 
+    // Tell the supervisor this exit was requested, not a crash, so it
+    // doesn't try to auto-restart.
+    inner.intentional_stop = true;
+
     if let Some(mut process) = inner.process.take() {
-        // Try graceful shutdown first
-        if let Err(e) = process.kill().await {
-            log::warn!("Failed to kill proxy process: {}", e);
+        let pid = process.id();
+
+        if let Some(pid) = pid {
+            request_graceful_exit(pid);
         }
 
-        // Wait for process to exit
-        let _ = tokio::time::timeout(
-            tokio::time::Duration::from_secs(2),
-            process.wait()
-        ).await;
-    }
+        // Give the process a chance to exit on its own first.
+        let exited_gracefully = tokio::time::timeout(GRACEFUL_SHUTDOWN_TIMEOUT, process.wait())
+            .await
+            .is_ok();
 
-    // Also try to kill by port (in case of orphan processes)
-    kill_process_on_port(inner.status.port).await;
+        if !exited_gracefully {
+            log::warn!(
+                "Proxy did not exit within {:?} of a graceful stop request, escalating to a forced kill",
+                GRACEFUL_SHUTDOWN_TIMEOUT
+            );
+            if let Err(e) = process.kill().await {
+                log::warn!("Failed to kill proxy process: {}", e);
+            }
+            // Reap the child so it doesn't linger as a zombie on Unix.
+            let _ = tokio::time::timeout(std::time::Duration::from_secs(2), process.wait()).await;
+
+            // Also try to kill by port, in case the forced kill above
+            // somehow missed it (e.g. it had already re-parented/forked).
+            kill_process_on_port(inner.status.port).await;
+        }
+    } else {
+        // We don't hold a `Child` handle for whatever is listening on the
+        // port (e.g. a process left over from a previous run of the app) -
+        // the port sweep is the only lever we have.
+        kill_process_on_port(inner.status.port).await;
+    }
 
     inner.status.running = false;
     inner.status.pid = None;
     inner.status.uptime_seconds = None;
+    inner.started_at = None;
 
     log::info!("Proxy stopped");
 
@@ -558,83 +990,73 @@ async fn kill_process_on_port(port: u16) {
     }
 }
 
-/// Download and install the proxy binary
-pub async fn install_binary(state: &Arc<Mutex<ProxyStateInner>>) -> Result<String, ProxyError> {
-    let mut inner = state.lock().await;
-
-    inner.is_downloading = true;
-    inner.download_progress = 0.0;
-    inner.last_error = None;
-
-    drop(inner); // Release lock for async operations
-
-    // Fetch latest release info
-    let release_url = format!(
-        "https://api.github.com/repos/{}/releases/latest",
-        GITHUB_REPO
-    );
-
-    let client = reqwest::Client::new();
-    let release: crate::models::GitHubRelease = client
-        .get(&release_url)
-        .header("Accept", "application/vnd.github.v3+json")
-        .header("User-Agent", "Zest/1.0")
-        .send()
-        .await
-        .map_err(|e| ProxyError::NetworkError(e.to_string()))?
-        .json()
-        .await
-        .map_err(|e| ProxyError::NetworkError(e.to_string()))?;
+/// Resolve the upstream proxy to use for outbound GitHub/asset requests and
+/// for the generated CLIProxyAPI config's `proxy-url` field. `ZEST_PROXY_URL`
+/// takes priority over the settings value; with neither set, `None` is
+/// returned and the HTTP client falls back to the OS's own
+/// `HTTP_PROXY`/`HTTPS_PROXY` environment, which `reqwest` honors by default.
+pub fn resolve_outbound_proxy(settings_proxy: Option<&str>) -> Option<String> {
+    std::env::var("ZEST_PROXY_URL")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .or_else(|| settings_proxy.filter(|v| !v.is_empty()).map(|v| v.to_string()))
+}
 
-    // Find compatible asset
-    let asset = find_compatible_asset(&release.assets)
-        .ok_or(ProxyError::NoCompatibleBinary)?;
+/// Shared configuration for every outbound HTTP client this module builds:
+/// an optional upstream proxy (see `resolve_outbound_proxy`), an optional
+/// custom CA bundle for TLS-inspecting corporate gateways (see
+/// `AppSettings::ca_bundle_path`), and how many redirects to follow.
+#[derive(Debug, Clone, Default)]
+pub struct HttpClientConfig {
+    pub outbound_proxy: Option<String>,
+    pub ca_bundle_path: Option<PathBuf>,
+    pub max_redirects: Option<usize>,
+}
 
-    // Update progress
-    {
-        let mut inner = state.lock().await;
-        inner.download_progress = 0.1;
+impl HttpClientConfig {
+    /// Just an upstream proxy, no custom CA bundle - the common case for
+    /// one-off requests that don't go through a running `ProxyStateInner`.
+    pub fn with_proxy(outbound_proxy: Option<&str>) -> Self {
+        Self {
+            outbound_proxy: outbound_proxy.map(|s| s.to_string()),
+            ..Default::default()
+        }
     }
+}
 
-    // Download the asset
-    let binary_data = client
-        .get(&asset.browser_download_url)
-        .header("User-Agent", "Zest/1.0")
-        .send()
-        .await
-        .map_err(|e| ProxyError::DownloadFailed(e.to_string()))?
-        .bytes()
-        .await
-        .map_err(|e| ProxyError::DownloadFailed(e.to_string()))?;
-
-    // Update progress
-    {
-        let mut inner = state.lock().await;
-        inner.download_progress = 0.7;
+/// Build an HTTP client per `config`: routes through `outbound_proxy` when
+/// set (http(s):// or socks5://, optionally with embedded basic-auth
+/// credentials, still honoring `NO_PROXY`), trusts the roots in
+/// `ca_bundle_path` in addition to the platform's own trust store when set,
+/// and caps redirects at `max_redirects` (default 10). With no proxy
+/// configured, the client still falls back to the OS's own
+/// `HTTP_PROXY`/`HTTPS_PROXY` environment, which `reqwest` honors by
+/// default.
+pub fn build_http_client(config: &HttpClientConfig) -> Result<reqwest::Client, ProxyError> {
+    let mut builder = reqwest::Client::builder();
+
+    if let Some(url) = &config.outbound_proxy {
+        let mut proxy = reqwest::Proxy::all(url).map_err(|e| ProxyError::NetworkError(e.to_string()))?;
+        if let Some(no_proxy) = reqwest::NoProxy::from_env() {
+            proxy = proxy.no_proxy(Some(no_proxy));
+        }
+        builder = builder.proxy(proxy);
     }
 
-    // Extract and install
-    extract_and_install(&binary_data, &asset.name).await?;
-
-    // Update progress
-    {
-        let mut inner = state.lock().await;
-        inner.download_progress = 1.0;
-        inner.is_downloading = false;
+    if let Some(path) = &config.ca_bundle_path {
+        let pem = std::fs::read(path)
+            .map_err(|e| ProxyError::NetworkError(format!("failed to read CA bundle {}: {}", path.display(), e)))?;
+        let cert = reqwest::Certificate::from_pem(&pem).map_err(|e| ProxyError::NetworkError(e.to_string()))?;
+        builder = builder.add_root_certificate(cert);
     }
 
-    // Extract version from tag
-    let version = release.tag_name.strip_prefix('v')
-        .unwrap_or(&release.tag_name)
-        .to_string();
-
-    log::info!("Installed CLIProxyAPI version {}", version);
+    builder = builder.redirect(reqwest::redirect::Policy::limited(config.max_redirects.unwrap_or(10)));
 
-    Ok(version)
+    builder.build().map_err(|e| ProxyError::NetworkError(e.to_string()))
 }
 
 /// Find a compatible asset for the current platform
-fn find_compatible_asset(assets: &[crate::models::GitHubAsset]) -> Option<&crate::models::GitHubAsset> {
+pub(crate) fn find_compatible_asset(assets: &[crate::models::GitHubAsset]) -> Option<&crate::models::GitHubAsset> {
     let (platform, arch) = if cfg!(target_os = "windows") {
         ("windows", if cfg!(target_arch = "aarch64") { "arm64" } else { "amd64" })
     } else if cfg!(target_os = "macos") {
@@ -663,106 +1085,43 @@ fn find_compatible_asset(assets: &[crate::models::GitHubAsset]) -> Option<&crate
     None
 }
 
-/// Extract and install the downloaded binary
-async fn extract_and_install(data: &[u8], asset_name: &str) -> Result<(), ProxyError> {
-    let temp_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
-    std::fs::create_dir_all(&temp_dir)
-        .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-
-    let downloaded_file = temp_dir.join(asset_name);
-    std::fs::write(&downloaded_file, data)
-        .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
+/// Does `name` (an archive entry's path) look like the CLIProxyAPI binary?
+/// Passed to `crate::archive::extract_entry` as the entry-matching predicate.
+fn is_binary_entry_name(name: &str) -> bool {
+    let base = std::path::Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    base == "cliproxyapi" || base == "cliproxyapi.exe" || base.contains("cli-proxy-api")
+}
 
+/// Extract and install the downloaded binary. Archives (`.tar.gz`/`.tgz`,
+/// `.tar.xz`, `.zip`) are detected by magic bytes and decoded entirely
+/// in-process via [`crate::archive`] rather than shelling out to
+/// `tar`/`unzip`/PowerShell, so installs work identically across platforms
+/// and on minimal systems that lack those tools.
+pub(crate) async fn extract_and_install(data: &[u8]) -> Result<(), ProxyError> {
     let data_dir = ProxyStateInner::data_dir();
-    std::fs::create_dir_all(&data_dir)
-        .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
 
     let binary_path = ProxyStateInner::binary_path();
+    let (binary_data, executable) = crate::archive::extract_entry(data, is_binary_entry_name).map_err(|e| {
+        counter!("zest_extraction_failures_total", "component" => "proxy").increment(1);
+        ProxyError::ExtractionFailed(e.to_string())
+    })?;
 
-    if asset_name.ends_with(".tar.gz") || asset_name.ends_with(".tgz") {
-        // Extract tar.gz
-        let output = Command::new("tar")
-            .args(["-xzf", downloaded_file.to_str().unwrap(), "-C", temp_dir.to_str().unwrap()])
-            .output()
-            .await
-            .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-
-        if !output.status.success() {
-            return Err(ProxyError::ExtractionFailed("tar extraction failed".to_string()));
-        }
-
-        // Find the binary in extracted files
-        if let Some(binary) = find_binary_in_directory(&temp_dir) {
-            if binary_path.exists() {
-                std::fs::remove_file(&binary_path)
-                    .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-            }
-            std::fs::copy(&binary, &binary_path)
-                .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-        } else {
-            return Err(ProxyError::ExtractionFailed("Binary not found in archive".to_string()));
-        }
-    } else if asset_name.ends_with(".zip") {
-        // Extract zip
-        #[cfg(unix)]
-        {
-            let output = Command::new("unzip")
-                .args(["-o", downloaded_file.to_str().unwrap(), "-d", temp_dir.to_str().unwrap()])
-                .output()
-                .await
-                .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-
-            if !output.status.success() {
-                return Err(ProxyError::ExtractionFailed("unzip extraction failed".to_string()));
-            }
-        }
-
-        #[cfg(windows)]
-        {
-            // Use PowerShell to extract on Windows
-            let output = Command::new("powershell")
-                .args([
-                    "-Command",
-                    &format!(
-                        "Expand-Archive -Path '{}' -DestinationPath '{}' -Force",
-                        downloaded_file.display(),
-                        temp_dir.display()
-                    ),
-                ])
-                .output()
-                .await
-                .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-
-            if !output.status.success() {
-                return Err(ProxyError::ExtractionFailed("PowerShell extraction failed".to_string()));
-            }
-        }
-
-        // Find the binary
-        if let Some(binary) = find_binary_in_directory(&temp_dir) {
-            if binary_path.exists() {
-                std::fs::remove_file(&binary_path)
-                    .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-            }
-            std::fs::copy(&binary, &binary_path)
-                .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-        } else {
-            return Err(ProxyError::ExtractionFailed("Binary not found in archive".to_string()));
-        }
-    } else {
-        // Direct binary file
-        if binary_path.exists() {
-            std::fs::remove_file(&binary_path)
-                .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
-        }
-        std::fs::copy(&downloaded_file, &binary_path)
-            .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
+    if binary_path.exists() {
+        std::fs::remove_file(&binary_path).map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
     }
+    std::fs::write(&binary_path, &binary_data).map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
 
-    // Make the binary executable (Unix only)
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
+        // The archive entry's executable bit is informational only here;
+        // we always ensure the installed binary is runnable regardless of
+        // what the archive recorded.
+        let _ = executable;
         std::fs::set_permissions(&binary_path, std::fs::Permissions::from_mode(0o755))
             .map_err(|e| ProxyError::ExtractionFailed(e.to_string()))?;
 
@@ -775,43 +1134,33 @@ async fn extract_and_install(data: &[u8], asset_name: &str) -> Result<(), ProxyE
                 .await;
         }
     }
-
-    // Cleanup temp directory
-    let _ = std::fs::remove_dir_all(&temp_dir);
+    #[cfg(not(unix))]
+    {
+        let _ = executable;
+    }
 
     Ok(())
 }
 
-/// Find the binary file in a directory (recursively)
-fn find_binary_in_directory(dir: &PathBuf) -> Option<PathBuf> {
-    let binary_names = ["CLIProxyAPI", "cli-proxy-api", "cli-proxy-api-plus", "CLIProxyAPI.exe"];
-
-    // First check for known binary names
-    for name in &binary_names {
-        let path = dir.join(name);
-        if path.exists() && path.is_file() {
-            return Some(path);
-        }
-    }
+/// Record a management-API call's latency and status-code bucket under
+/// `zest_management_api_duration_ms`/`zest_management_api_requests_total`,
+/// tagged by `endpoint` (e.g. `"auth-files"`, `"api-keys"`).
+fn record_management_api_call(endpoint: &'static str, started: std::time::Instant, status: reqwest::StatusCode) {
+    histogram!("zest_management_api_duration_ms", "endpoint" => endpoint).record(started.elapsed().as_millis() as f64);
+    counter!("zest_management_api_requests_total", "endpoint" => endpoint, "status" => status.as_u16().to_string())
+        .increment(1);
+}
 
-    // Recursively search subdirectories
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                if let Some(found) = find_binary_in_directory(&path) {
-                    return Some(found);
-                }
-            } else if path.is_file() {
-                let name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
-                if name.contains("cliproxyapi") || name.contains("cli-proxy-api") {
-                    return Some(path);
-                }
-            }
-        }
+/// Set the per-auth-file `zest_auth_file_available`/`zest_auth_file_disabled`
+/// gauges (tagged by `name`/`provider`) from the management API's latest
+/// response, so `unavailable`/`disabled` auth files can be alerted on.
+fn record_auth_file_gauges(files: &[AuthFile]) {
+    for file in files {
+        gauge!("zest_auth_file_available", "name" => file.name.clone(), "provider" => file.provider.clone())
+            .set(if file.unavailable { 0.0 } else { 1.0 });
+        gauge!("zest_auth_file_disabled", "name" => file.name.clone(), "provider" => file.provider.clone())
+            .set(if file.disabled { 1.0 } else { 0.0 });
     }
-
-    None
 }
 
 /// Fetch auth files from the management API
@@ -827,14 +1176,18 @@ pub async fn fetch_auth_files(state: &Arc<Mutex<ProxyStateInner>>) -> Result<Vec
     let url = format!("{}/auth-files", inner.management_url());
     log::debug!("Fetching auth files from: {}", url);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_default();
+    let client = build_http_client(&HttpClientConfig {
+        outbound_proxy: inner.outbound_proxy.clone(),
+        ca_bundle_path: inner.ca_bundle_path.clone(),
+        max_redirects: None,
+    })
+    .unwrap_or_default();
 
+    let started = std::time::Instant::now();
     let response = match client
         .get(&url)
         .header("Authorization", format!("Bearer {}", inner.management_key))
+        .timeout(std::time::Duration::from_secs(5))
         .send()
         .await
     {
@@ -845,12 +1198,14 @@ pub async fn fetch_auth_files(state: &Arc<Mutex<ProxyStateInner>>) -> Result<Vec
             return Ok(Vec::new());
         }
     };
+    record_management_api_call("auth-files", started, response.status());
 
     // If we get 401, try to re-read the management key and retry once
     if response.status() == reqwest::StatusCode::UNAUTHORIZED
         || response.status() == reqwest::StatusCode::FORBIDDEN
     {
         log::warn!("Auth files request got {} - trying to refresh management key", response.status());
+        counter!("zest_management_api_key_refresh_total", "endpoint" => "auth-files").increment(1);
 
         // Try to get the correct key from UserDefaults
         if let Some(new_key) = ProxyStateInner::read_management_key_from_defaults() {
@@ -859,9 +1214,11 @@ pub async fn fetch_auth_files(state: &Arc<Mutex<ProxyStateInner>>) -> Result<Vec
                 inner.management_key = new_key.clone();
 
                 // Retry with new key
+                let retry_started = std::time::Instant::now();
                 let retry_response = match client
                     .get(&url)
                     .header("Authorization", format!("Bearer {}", new_key))
+                    .timeout(std::time::Duration::from_secs(5))
                     .send()
                     .await
                 {
@@ -871,6 +1228,7 @@ pub async fn fetch_auth_files(state: &Arc<Mutex<ProxyStateInner>>) -> Result<Vec
                         return Ok(Vec::new());
                     }
                 };
+                record_management_api_call("auth-files", retry_started, retry_response.status());
 
                 if retry_response.status().is_success() {
                     let response_data: AuthFilesResponse = match retry_response.json().await {
@@ -881,6 +1239,7 @@ pub async fn fetch_auth_files(state: &Arc<Mutex<ProxyStateInner>>) -> Result<Vec
                         }
                     };
                     log::debug!("Fetched {} auth files from proxy (after key refresh)", response_data.files.len());
+                    record_auth_file_gauges(&response_data.files);
                     return Ok(response_data.files);
                 }
             }
@@ -916,6 +1275,7 @@ pub async fn fetch_auth_files(state: &Arc<Mutex<ProxyStateInner>>) -> Result<Vec
         );
     }
 
+    record_auth_file_gauges(&response_data.files);
     Ok(response_data.files)
 }
 
@@ -928,17 +1288,23 @@ pub async fn fetch_api_keys(state: &Arc<Mutex<ProxyStateInner>>) -> Result<Vec<S
     }
 
     let url = format!("{}/api-keys", inner.management_url());
-    let client = reqwest::Client::new();
-
-    let response: ApiKeysResponse = client
+    let client = build_http_client(&HttpClientConfig {
+        outbound_proxy: inner.outbound_proxy.clone(),
+        ca_bundle_path: inner.ca_bundle_path.clone(),
+        max_redirects: None,
+    })
+    .map_err(|e| ProxyError::ApiError(e.to_string()))?;
+
+    let started = std::time::Instant::now();
+    let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", inner.management_key))
         .send()
         .await
-        .map_err(|e| ProxyError::ApiError(e.to_string()))?
-        .json()
-        .await
         .map_err(|e| ProxyError::ApiError(e.to_string()))?;
+    record_management_api_call("api-keys", started, response.status());
+
+    let response: ApiKeysResponse = response.json().await.map_err(|e| ProxyError::ApiError(e.to_string()))?;
 
     Ok(response.api_keys)
 }
@@ -952,10 +1318,12 @@ pub async fn check_health(state: &Arc<Mutex<ProxyStateInner>>) -> bool {
     }
 
     let url = format!("{}/meta", inner.base_url());
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_default();
-
-    client.get(&url).send().await.is_ok()
+    let client = build_http_client(&HttpClientConfig {
+        outbound_proxy: inner.outbound_proxy.clone(),
+        ca_bundle_path: inner.ca_bundle_path.clone(),
+        max_redirects: None,
+    })
+    .unwrap_or_default();
+
+    client.get(&url).timeout(std::time::Duration::from_secs(5)).send().await.is_ok()
 }
@@ -0,0 +1,122 @@
+//! Secret strings with debug redaction and optional at-rest encryption.
+//!
+//! `RemoteManagementConfig::secret_key` and `AppConfig::api_keys` used to be
+//! plain `String`s, which meant they leaked through the derived `Debug` impl
+//! and always round-tripped to `config.yaml` in cleartext. `SecretString`
+//! wraps `secrecy`'s `SecretString` so `Debug` always prints `[REDACTED]`,
+//! and `AppConfig::seal`/`unseal` (see `models.rs`) use [`seal_value`]/
+//! [`unseal_value`] here to transparently encrypt/decrypt these fields with
+//! AES-256-GCM (random 96-bit nonce prepended to ciphertext, base64-encoded)
+//! whenever a `ZEST_MASTER_KEY` env var is present. An `enc:` prefix marks
+//! an already-encrypted value, so configs written without a master key set
+//! keep round-tripping as plaintext.
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use rand::RngCore;
+use secrecy::{ExposeSecret, SecretString as SecrecySecretString};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const NONCE_LEN: usize = 12;
+const ENC_PREFIX: &str = "enc:";
+
+/// A string that redacts in `Debug` and can be transparently sealed at rest.
+#[derive(Clone)]
+pub struct SecretString(SecrecySecretString);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        Self(SecrecySecretString::from(value))
+    }
+
+    pub fn expose(&self) -> &str {
+        self.0.expose_secret()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expose().is_empty()
+    }
+}
+
+impl Default for SecretString {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.expose())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(SecretString::new(String::deserialize(deserializer)?))
+    }
+}
+
+/// Derive a 32-byte AES key from `ZEST_MASTER_KEY`, if set, by hashing it
+/// with SHA-256 so any length/format of passphrase works.
+pub fn master_key() -> Option<[u8; 32]> {
+    let raw = std::env::var("ZEST_MASTER_KEY").ok()?;
+    if raw.is_empty() {
+        return None;
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(raw.as_bytes());
+    Some(hasher.finalize().into())
+}
+
+/// Encrypt `value` under `key`, returning `enc:base64(nonce || ciphertext)`.
+/// Already-sealed (or empty) values are returned unchanged.
+pub fn seal_value(value: &str, key: &[u8; 32]) -> Result<String, String> {
+    if value.is_empty() || value.starts_with(ENC_PREFIX) {
+        return Ok(value.to_string());
+    }
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, value.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut combined = nonce_bytes.to_vec();
+    combined.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENC_PREFIX, base64::engine::general_purpose::STANDARD.encode(combined)))
+}
+
+/// Decrypt a value produced by [`seal_value`]. Values without the `enc:`
+/// prefix are assumed to already be plaintext and are returned unchanged.
+pub fn unseal_value(value: &str, key: &[u8; 32]) -> Result<String, String> {
+    let Some(encoded) = value.strip_prefix(ENC_PREFIX) else {
+        return Ok(value.to_string());
+    };
+
+    let combined = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| e.to_string())?;
+    if combined.len() < NONCE_LEN {
+        return Err("ciphertext too short".to_string());
+    }
+    let (nonce_bytes, ciphertext) = combined.split_at(NONCE_LEN);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|e| e.to_string())?;
+
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
@@ -3,13 +3,31 @@
 //! Handles application settings persistence.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tauri::Emitter;
 use tokio::sync::Mutex;
 
+/// Env var prefix consulted by the layered settings resolver
+const ENV_PREFIX: &str = "ZEST_";
+
+/// Current schema version of `AppSettings`. Bump this and add a
+/// `migrate_vN_to_vN+1` entry to `MIGRATIONS` whenever a field is renamed or
+/// its type changes, so older settings files upgrade instead of failing to
+/// deserialize.
+const CURRENT_SETTINGS_VERSION: u32 = 3;
+
+fn default_settings_version() -> u32 {
+    CURRENT_SETTINGS_VERSION
+}
+
 /// Application settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct AppSettings {
+    /// Schema version, used to migrate older settings.json files forward
+    #[serde(default = "default_settings_version")]
+    pub version: u32,
     /// Proxy port
     pub port: u16,
     /// Allow network access (bind to 0.0.0.0)
@@ -32,11 +50,53 @@ pub struct AppSettings {
     pub language: String,
     /// Proxy URL for outgoing connections
     pub proxy_url: String,
+    /// Port the Prometheus `/metrics` endpoint is served on
+    #[serde(default = "default_metrics_port")]
+    pub metrics_port: u16,
+    /// External credential-process executable backing `store_credential`/
+    /// `get_credential`/`delete_credential`, superseding the OS keychain
+    /// when set
+    #[serde(default)]
+    pub credential_process: Option<crate::credential_process::CredentialProcessConfig>,
+    /// 1Password CLI backend configuration, superseding both the OS
+    /// keychain and `credential_process` when set
+    #[serde(default)]
+    pub onepassword: Option<crate::onepassword::OnePasswordConfig>,
+    /// Verify the downloaded proxy binary against its release's published
+    /// SHA-256 checksum before installing it. Missing checksum assets are
+    /// always a soft warning either way; this only controls whether a
+    /// checksum that IS published gets checked.
+    #[serde(default = "default_verify_checksums")]
+    pub verify_checksums: bool,
+    /// Upstream HTTP(S)/SOCKS5 proxy used for GitHub API calls and release
+    /// asset downloads, and written into the generated CLIProxyAPI
+    /// `proxy-url` config field so the managed binary routes its own
+    /// upstream requests through it too. `ZEST_PROXY_URL` overrides this
+    /// when set; with neither set, the OS's own `HTTP_PROXY`/`HTTPS_PROXY`
+    /// environment is still honored by the underlying HTTP client.
+    #[serde(default)]
+    pub outbound_proxy_url: Option<String>,
+    /// Path to a PEM bundle of additional trusted root certificates,
+    /// installed into every outbound HTTP client this app builds (GitHub
+    /// API/asset downloads, the management API). Lets the proxy manager
+    /// work behind a TLS-inspecting corporate gateway without disabling
+    /// certificate validation entirely.
+    #[serde(default)]
+    pub ca_bundle_path: Option<String>,
+}
+
+fn default_verify_checksums() -> bool {
+    true
+}
+
+fn default_metrics_port() -> u16 {
+    9317
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            version: CURRENT_SETTINGS_VERSION,
             port: 8317,
             allow_network_access: false,
             use_bridge_mode: true,
@@ -48,6 +108,12 @@ impl Default for AppSettings {
             theme: "system".to_string(),
             language: "en".to_string(),
             proxy_url: String::new(),
+            metrics_port: default_metrics_port(),
+            credential_process: None,
+            onepassword: None,
+            verify_checksums: default_verify_checksums(),
+            outbound_proxy_url: None,
+            ca_bundle_path: None,
         }
     }
 }
@@ -59,7 +125,13 @@ pub struct SettingsState {
 
 impl SettingsState {
     pub fn new() -> Self {
-        let settings = load_settings().unwrap_or_default();
+        let settings = match load_settings() {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::warn!("Failed to load settings.json, falling back to defaults: {}", e);
+                AppSettings::default()
+            }
+        };
         Self {
             inner: Arc::new(Mutex::new(settings)),
         }
@@ -72,27 +144,332 @@ impl Default for SettingsState {
     }
 }
 
-/// Get the settings file path
-fn settings_path() -> PathBuf {
+/// A single schema migration, taking the raw JSON at version `from` (the
+/// first element of its `MIGRATIONS` entry) and returning it upgraded to
+/// version `from + 1`.
+type Migration = fn(serde_json::Value) -> serde_json::Value;
+
+/// Ordered migrations, keyed by the version they migrate *from*. Applied
+/// one at a time until the value reaches `CURRENT_SETTINGS_VERSION`.
+const MIGRATIONS: &[(u32, Migration)] = &[(1, migrate_v1_to_v2), (2, migrate_v2_to_v3)];
+
+/// v1 -> v2: inject the `version` field (absent in the original unversioned
+/// schema) and default any keys introduced since. Serde's `#[serde(default)]`
+/// already covers new optional fields, so this migration only needs to stamp
+/// the version.
+fn migrate_v1_to_v2(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("version".to_string(), serde_json::json!(2));
+    }
+    value
+}
+
+/// v2 -> v3: stamp the version for the newly introduced `metrics_port`
+/// field; `#[serde(default)]` fills in its value on existing files.
+fn migrate_v2_to_v3(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("version".to_string(), serde_json::json!(3));
+    }
+    value
+}
+
+/// Run the migration chain over a raw settings value until it reaches
+/// `CURRENT_SETTINGS_VERSION`, or until no migration exists for its current
+/// version (in which case deserialization is left to fail naturally so the
+/// error surfaces instead of looping forever). Returns the migrated value and
+/// whether any migration actually ran.
+fn migrate_to_current(mut value: serde_json::Value) -> (serde_json::Value, bool) {
+    let mut version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(1);
+    let mut migrated = false;
+
+    while version < CURRENT_SETTINGS_VERSION {
+        match MIGRATIONS.iter().find(|(from, _)| *from == version) {
+            Some((_, migrate)) => {
+                value = migrate(value);
+                version += 1;
+                migrated = true;
+            }
+            None => break,
+        }
+    }
+
+    (value, migrated)
+}
+
+/// Get the Zest data directory. Honors a `ZEST_DATA_HOME` override (same
+/// idea as `paths::config_root`'s `ZEST_CONFIG_HOME`) so tests can point
+/// `settings.json` at a sandbox directory instead of the real one.
+fn zest_data_dir() -> PathBuf {
+    if let Ok(override_dir) = std::env::var("ZEST_DATA_HOME") {
+        return PathBuf::from(override_dir);
+    }
     dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("Zest")
-        .join("settings.json")
 }
 
-/// Load settings from disk
-pub fn load_settings() -> Result<AppSettings, Box<dyn std::error::Error>> {
-    let path = settings_path();
-    if !path.exists() {
-        return Ok(AppSettings::default());
+/// Get the settings file path
+fn settings_path() -> PathBuf {
+    zest_data_dir().join("settings.json")
+}
+
+/// Directory holding named settings profiles (`profiles/<name>.json`)
+fn profiles_dir() -> PathBuf {
+    zest_data_dir().join("profiles")
+}
+
+/// Path to the pointer file recording which profile is currently active
+fn active_profile_path() -> PathBuf {
+    zest_data_dir().join("active_profile")
+}
+
+fn profile_path(name: &str) -> PathBuf {
+    profiles_dir().join(format!("{}.json", name))
+}
+
+/// List the names of all saved settings profiles
+pub fn list_profiles() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(profiles_dir()) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                path.file_stem().map(|s| s.to_string_lossy().to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    names.sort();
+    names
+}
+
+/// Persist `settings` as a named profile
+pub fn save_profile(name: &str, settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = profiles_dir();
+    std::fs::create_dir_all(&dir)?;
+    let content = serde_json::to_string_pretty(settings)?;
+    std::fs::write(profile_path(name), content)?;
+    Ok(())
+}
+
+/// Load a named profile's settings
+pub fn load_profile(name: &str) -> Result<AppSettings, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(profile_path(name))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Delete a named profile. Clears the active-profile pointer if it pointed
+/// at the deleted profile.
+pub fn delete_profile(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let path = profile_path(name);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    if active_profile().as_deref() == Some(name) {
+        let _ = std::fs::remove_file(active_profile_path());
     }
+    Ok(())
+}
+
+/// The currently active profile name, if one has been activated
+pub fn active_profile() -> Option<String> {
+    std::fs::read_to_string(active_profile_path())
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
 
-    let content = std::fs::read_to_string(&path)?;
-    let settings: AppSettings = serde_json::from_str(&content)?;
+/// Switch the active profile pointer to `name` and swap `settings` in to
+/// become the effective settings (the port, routing strategy, and proxy URL
+/// in particular often differ between environments like "Work" vs.
+/// "Local-only").
+pub fn activate_profile(name: &str) -> Result<AppSettings, Box<dyn std::error::Error>> {
+    let settings = load_profile(name)?;
+    std::fs::create_dir_all(zest_data_dir())?;
+    std::fs::write(active_profile_path(), name)?;
     Ok(settings)
 }
 
+/// Where a resolved setting's value ultimately came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SettingSource {
+    Default,
+    User,
+    Project,
+    Environment,
+}
+
+/// Records which layer each top-level field of the resolved `AppSettings`
+/// came from, so the UI can show e.g. "overridden by environment".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsSources {
+    pub fields: HashMap<String, SettingSource>,
+}
+
+impl SettingsSources {
+    fn record(&mut self, overlay: &serde_json::Value, source: SettingSource) {
+        if let Some(obj) = overlay.as_object() {
+            for key in obj.keys() {
+                self.fields.insert(key.clone(), source);
+            }
+        }
+    }
+
+    pub fn source_of(&self, field: &str) -> SettingSource {
+        self.fields.get(field).copied().unwrap_or(SettingSource::Default)
+    }
+}
+
+/// Get the project-local settings override path (cwd/.zest/settings.json)
+fn project_settings_path() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(".zest")
+        .join("settings.json")
+}
+
+/// Deep-merge `overlay` into `base`: any non-null key present in `overlay`
+/// replaces the corresponding key in `base`, recursing into nested objects.
+fn merge_json(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base_map), serde_json::Value::Object(overlay_map)) => {
+            for (key, overlay_value) in overlay_map {
+                if overlay_value.is_null() {
+                    continue;
+                }
+                match base_map.get_mut(&key) {
+                    Some(base_value) if base_value.is_object() && overlay_value.is_object() => {
+                        merge_json(base_value, overlay_value);
+                    }
+                    _ => {
+                        base_map.insert(key, overlay_value);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A genuine parse error in a settings layer, as opposed to the file simply
+/// not existing. Carries line/column so a hand-edited `settings.json` with a
+/// stray comma can be diagnosed instead of silently discarded.
+#[derive(Debug, thiserror::Error)]
+#[error("{path}: {message} (line {line}, column {column})")]
+pub struct SettingsParseError {
+    pub path: PathBuf,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Read a layer's JSON contents from disk, if present, as a loose `Value`.
+///
+/// Tolerates `//` and `/* */` comments and trailing commas (the same lenient
+/// dialect Zed accepts for its settings store), so a hand-edited file with a
+/// stray comma doesn't silently reset the layer to nothing. Returns `Ok(None)`
+/// when the file is simply absent, and `Err` only on a genuine parse failure.
+fn read_layer(path: &PathBuf) -> Result<Option<serde_json::Value>, SettingsParseError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Ok(None),
+    };
+
+    serde_json_lenient::from_str(&content)
+        .map(Some)
+        .map_err(|e| SettingsParseError {
+            path: path.clone(),
+            message: e.to_string(),
+            line: e.line(),
+            column: e.column(),
+        })
+}
+
+/// Build an overlay `Value` from `ZEST_`-prefixed environment variables,
+/// e.g. `ZEST_PORT=9000` -> `{"port": 9000}`, `ZEST_ALLOW_NETWORK_ACCESS=true` -> `{"allow_network_access": true}`.
+fn env_overlay() -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+
+    for (name, value) in std::env::vars() {
+        if let Some(field) = name.strip_prefix(ENV_PREFIX) {
+            let field = field.to_lowercase();
+            let parsed = serde_json::from_str(&value)
+                .unwrap_or_else(|_| serde_json::Value::String(value));
+            map.insert(field, parsed);
+        }
+    }
+
+    serde_json::Value::Object(map)
+}
+
+/// Resolve the effective `AppSettings` by merging, in priority order:
+/// (1) compiled defaults, (2) the user settings file, (3) an optional
+/// project-local `.zest/settings.json`, (4) `ZEST_`-prefixed env vars.
+/// Returns the resolved settings alongside a record of which layer each
+/// field came from.
+pub fn load_settings_with_sources() -> Result<(AppSettings, SettingsSources), Box<dyn std::error::Error>> {
+    let mut sources = SettingsSources::default();
+    let mut merged = serde_json::to_value(AppSettings::default())?;
+    let mut migrated = false;
+
+    // Migrate the user file's raw `Value` *before* merging it into
+    // `merged`: `merged` already carries `version: CURRENT_SETTINGS_VERSION`
+    // from the defaults layer, so migrating after the merge would see a
+    // versionless v1 file as already-current and silently skip every
+    // migration.
+    if let Some(user_layer) = read_layer(&settings_path())? {
+        let (user_layer, user_migrated) = migrate_to_current(user_layer);
+        migrated = user_migrated;
+        sources.record(&user_layer, SettingSource::User);
+        merge_json(&mut merged, user_layer);
+    }
+
+    if let Some(project_layer) = read_layer(&project_settings_path())? {
+        sources.record(&project_layer, SettingSource::Project);
+        merge_json(&mut merged, project_layer);
+    }
+
+    let env_layer = env_overlay();
+    sources.record(&env_layer, SettingSource::Environment);
+    merge_json(&mut merged, env_layer);
+
+    let settings: AppSettings = serde_json::from_value(merged)?;
+
+    if migrated {
+        log::info!("Migrated settings.json to schema version {}", CURRENT_SETTINGS_VERSION);
+        if let Err(e) = save_settings(&settings) {
+            log::warn!("Failed to persist migrated settings.json: {}", e);
+        }
+    }
+
+    Ok((settings, sources))
+}
+
+/// Load settings from disk, merging the layered sources (see
+/// [`load_settings_with_sources`]) and discarding the provenance record.
+pub fn load_settings() -> Result<AppSettings, Box<dyn std::error::Error>> {
+    load_settings_with_sources().map(|(settings, _)| settings)
+}
+
 /// Save settings to disk
+///
+/// Writes `settings` as given to the user file. Values that came from the
+/// project file or `ZEST_` env vars are not separated out, so saving a value
+/// obtained from [`load_settings`] (as the migration path in
+/// [`load_settings_with_sources`] does) writes those overrides into the user
+/// file too. Also (re)writes the sibling `settings.schema.json` so editors
+/// can offer autocompletion.
 pub fn save_settings(settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>> {
     let path = settings_path();
 
@@ -101,9 +478,58 @@ pub fn save_settings(settings: &AppSettings) -> Result<(), Box<dyn std::error::E
         std::fs::create_dir_all(parent)?;
     }
 
-    let content = serde_json::to_string_pretty(settings)?;
+    let mut value = serde_json::to_value(settings)?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("$schema".to_string(), serde_json::json!("./settings.schema.json"));
+    }
+
+    let content = serde_json::to_string_pretty(&value)?;
     std::fs::write(&path, content)?;
 
+    if let Err(e) = write_schema_file() {
+        log::warn!("Failed to write settings.schema.json: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Get the settings schema file path, alongside `settings_path()`
+fn schema_path() -> PathBuf {
+    settings_path()
+        .parent()
+        .map(|p| p.join("settings.schema.json"))
+        .unwrap_or_else(|| PathBuf::from("settings.schema.json"))
+}
+
+/// Derive a JSON Schema for `AppSettings`, with the `theme` and
+/// `routing_strategy` string fields further constrained to their valid
+/// values so a hand-edited `settings.json` gets flagged by editor tooling.
+pub fn settings_schema() -> serde_json::Value {
+    let schema = schemars::schema_for!(AppSettings);
+    let mut value = serde_json::to_value(schema).unwrap_or_default();
+
+    if let Some(properties) = value
+        .get_mut("properties")
+        .and_then(|p| p.as_object_mut())
+    {
+        if let Some(theme) = properties.get_mut("theme") {
+            theme["enum"] = serde_json::json!(["light", "dark", "system"]);
+        }
+        if let Some(routing_strategy) = properties.get_mut("routing_strategy") {
+            routing_strategy["enum"] =
+                serde_json::json!(["round-robin", "least-connections", "random", "failover"]);
+        }
+    }
+
+    value
+}
+
+fn write_schema_file() -> Result<(), Box<dyn std::error::Error>> {
+    let path = schema_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&settings_schema())?)?;
     Ok(())
 }
 
@@ -118,12 +544,76 @@ pub async fn get_setting<T: serde::de::DeserializeOwned>(
     serde_json::from_value(field.clone()).ok()
 }
 
+/// Named routing strategies accepted for the `routing_strategy` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoutingStrategy {
+    RoundRobin,
+    LeastConnections,
+    Random,
+    Failover,
+}
+
+/// Selectable UI themes accepted for the `theme` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    System,
+}
+
+/// A proxy port in the valid TCP range, rejecting `0` (the OS's "pick any
+/// free port" sentinel, which the UI has no way to discover afterwards).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyPort(u16);
+
+impl ProxyPort {
+    pub fn new(port: u16) -> Result<Self, String> {
+        if port == 0 {
+            return Err("port must not be 0".to_string());
+        }
+        Ok(Self(port))
+    }
+
+    pub fn get(self) -> u16 {
+        self.0
+    }
+}
+
+/// Validate an incoming `update_setting` value against the target field's
+/// type, so a frontend bug can't silently persist a nonsense value (e.g. an
+/// unrecognized `routing_strategy`, or a `port` of 0).
+fn validate_setting_field(key: &str, value: &serde_json::Value) -> Result<(), String> {
+    match key {
+        "routing_strategy" => serde_json::from_value::<RoutingStrategy>(value.clone())
+            .map(|_| ())
+            .map_err(|_| {
+                format!(
+                    "invalid routing_strategy {value}: expected one of round-robin, least-connections, random, failover"
+                )
+            }),
+        "theme" => serde_json::from_value::<Theme>(value.clone())
+            .map(|_| ())
+            .map_err(|_| format!("invalid theme {value}: expected one of light, dark, system")),
+        "port" => {
+            let port: u16 = serde_json::from_value(value.clone())
+                .map_err(|_| format!("invalid port {value}: expected an integer between 1 and 65535"))?;
+            ProxyPort::new(port)?;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Update a specific setting value
 pub async fn update_setting(
     state: &Arc<Mutex<AppSettings>>,
     key: &str,
     value: serde_json::Value,
 ) -> Result<(), String> {
+    validate_setting_field(key, &value)?;
+
     let mut settings = state.lock().await;
     let mut settings_value = serde_json::to_value(&*settings).map_err(|e| e.to_string())?;
 
@@ -136,3 +626,208 @@ pub async fn update_setting(
 
     Ok(())
 }
+
+/// Hash a settings file's contents so the watcher can tell a genuine
+/// external edit apart from re-observing a write it already applied.
+fn hash_content(content: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Build a sparse diff of the top-level fields that changed between two
+/// `AppSettings` snapshots, as `{field: {"before": ..., "after": ...}}`.
+fn diff_settings(before: &AppSettings, after: &AppSettings) -> serde_json::Value {
+    let before_value = serde_json::to_value(before).unwrap_or_default();
+    let after_value = serde_json::to_value(after).unwrap_or_default();
+    let mut diff = serde_json::Map::new();
+
+    if let (Some(before_obj), Some(after_obj)) = (before_value.as_object(), after_value.as_object()) {
+        for (key, after_field) in after_obj {
+            if before_obj.get(key) != Some(after_field) {
+                diff.insert(
+                    key.clone(),
+                    serde_json::json!({ "before": before_obj.get(key), "after": after_field }),
+                );
+            }
+        }
+    }
+
+    serde_json::Value::Object(diff)
+}
+
+/// Watch `settings.json` for external edits (made by hand, or by another
+/// Zest window) and hot-reload them into the running `AppSettings`.
+///
+/// Runs on a dedicated thread since `notify`'s callback fires off the async
+/// runtime; debounces rapid successive writes (coalescing within ~200ms) and
+/// skips reacting to a write whose content we've already applied, so our own
+/// `save_settings` calls don't cause a reload loop.
+pub fn watch_settings_file<R: tauri::Runtime>(app: tauri::AppHandle<R>, inner: Arc<Mutex<AppSettings>>) {
+    use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+    use std::sync::mpsc::channel;
+
+    std::thread::spawn(move || {
+        let path = settings_path();
+        let Some(watch_dir) = path.parent().map(|p| p.to_path_buf()) else {
+            return;
+        };
+        let _ = std::fs::create_dir_all(&watch_dir);
+
+        let (tx, rx) = channel();
+        let mut watcher: RecommendedWatcher = match notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                let _ = tx.send(event);
+            }
+        }) {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("Failed to start settings.json watcher: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = watcher.watch(&watch_dir, RecursiveMode::NonRecursive) {
+            log::warn!("Failed to watch settings directory {:?}: {}", watch_dir, e);
+            return;
+        }
+
+        let mut last_hash = std::fs::read_to_string(&path).ok().map(|c| hash_content(&c));
+        let debounce = std::time::Duration::from_millis(200);
+
+        while let Ok(event) = rx.recv() {
+            if !event.paths.iter().any(|p| p == &path) {
+                continue;
+            }
+
+            // Coalesce a burst of events from a single editor save.
+            std::thread::sleep(debounce);
+            while rx.try_recv().is_ok() {}
+
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let new_hash = hash_content(&content);
+            if last_hash == Some(new_hash) {
+                continue;
+            }
+            last_hash = Some(new_hash);
+
+            let (new_settings, _sources) = match load_settings_with_sources() {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    log::warn!("Ignoring unparseable settings.json reload: {}", e);
+                    continue;
+                }
+            };
+
+            let before = inner.blocking_lock().clone();
+            let diff = diff_settings(&before, &new_settings);
+            if diff.as_object().map(|o| o.is_empty()).unwrap_or(true) {
+                continue;
+            }
+
+            *inner.blocking_lock() = new_settings.clone();
+            log::info!("Reloaded settings.json after external edit");
+            let _ = app.emit(
+                "settings-changed",
+                serde_json::json!({ "settings": new_settings, "diff": diff }),
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_v1_settings_round_trips_to_current_shape() {
+        let v1 = serde_json::json!({
+            "port": 9001,
+            "allow_network_access": true,
+            "use_bridge_mode": false,
+            "logging_to_file": false,
+            "routing_strategy": "random",
+            "launch_at_login": false,
+            "show_in_tray": true,
+            "menu_bar_provider": null,
+            "theme": "dark",
+            "language": "en",
+            "proxy_url": "",
+        });
+
+        let (migrated, changed) = migrate_to_current(v1);
+        assert!(changed);
+        assert_eq!(
+            migrated.get("version").and_then(|v| v.as_u64()),
+            Some(CURRENT_SETTINGS_VERSION as u64)
+        );
+
+        let settings: AppSettings = serde_json::from_value(migrated).unwrap();
+        assert_eq!(settings.version, CURRENT_SETTINGS_VERSION);
+        assert_eq!(settings.port, 9001);
+        assert_eq!(settings.routing_strategy, "random");
+    }
+
+    #[test]
+    fn test_migrate_current_settings_is_a_no_op() {
+        let current = serde_json::to_value(AppSettings::default()).unwrap();
+        let (_, changed) = migrate_to_current(current);
+        assert!(!changed);
+    }
+
+    #[test]
+    fn test_validate_setting_field_rejects_unknown_routing_strategy() {
+        let err = validate_setting_field("routing_strategy", &serde_json::json!("nonsense")).unwrap_err();
+        assert!(err.contains("routing_strategy"));
+    }
+
+    #[test]
+    fn test_validate_setting_field_rejects_port_zero() {
+        let err = validate_setting_field("port", &serde_json::json!(0)).unwrap_err();
+        assert!(err.contains("port"));
+    }
+
+    /// Guards against the migration running against the defaults-seeded
+    /// `merged` value instead of the raw user file: a versionless v1 file
+    /// on disk must come back migrated (and get rewritten) rather than
+    /// silently treated as already-current.
+    #[test]
+    fn test_load_settings_with_sources_migrates_v1_file_on_disk() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "zest-settings-test-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        std::env::set_var("ZEST_DATA_HOME", &data_dir);
+
+        let v1_file = serde_json::json!({
+            "port": 9001,
+            "routing_strategy": "random",
+        });
+        std::fs::write(settings_path(), serde_json::to_vec(&v1_file).unwrap()).unwrap();
+
+        let result = load_settings_with_sources();
+
+        // Restore before any assertion can early-return and leak the
+        // override into other tests.
+        std::env::remove_var("ZEST_DATA_HOME");
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        let (settings, _sources) = result.unwrap();
+        assert_eq!(settings.version, CURRENT_SETTINGS_VERSION);
+        assert_eq!(settings.port, 9001);
+        assert_eq!(settings.routing_strategy, "random");
+    }
+
+    #[test]
+    fn test_validate_setting_field_accepts_known_values() {
+        assert!(validate_setting_field("routing_strategy", &serde_json::json!("least-connections")).is_ok());
+        assert!(validate_setting_field("theme", &serde_json::json!("dark")).is_ok());
+        assert!(validate_setting_field("port", &serde_json::json!(8317)).is_ok());
+    }
+}
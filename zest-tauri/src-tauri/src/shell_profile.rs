@@ -68,91 +68,6 @@ impl ShellType {
     }
 }
 
-/// CLI Agent types that can be configured
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum CLIAgent {
-    ClaudeCode,
-    GeminiCLI,
-    Codex,
-    Qwen,
-    Iflow,
-    Antigravity,
-}
-
-impl CLIAgent {
-    pub fn display_name(&self) -> &'static str {
-        match self {
-            CLIAgent::ClaudeCode => "Claude Code",
-            CLIAgent::GeminiCLI => "Gemini CLI",
-            CLIAgent::Codex => "Codex",
-            CLIAgent::Qwen => "Qwen",
-            CLIAgent::Iflow => "iFlow",
-            CLIAgent::Antigravity => "Antigravity",
-        }
-    }
-
-    /// Get the environment variable name for this agent's base URL
-    pub fn env_var_name(&self) -> &'static str {
-        match self {
-            CLIAgent::ClaudeCode => "ANTHROPIC_BASE_URL",
-            CLIAgent::GeminiCLI => "GEMINI_API_BASE",
-            CLIAgent::Codex => "OPENAI_BASE_URL",
-            CLIAgent::Qwen => "QWEN_BASE_URL",
-            CLIAgent::Iflow => "IFLOW_BASE_URL",
-            CLIAgent::Antigravity => "ANTIGRAVITY_BASE_URL",
-        }
-    }
-
-    /// Get the API key environment variable name for this agent
-    pub fn api_key_env_var(&self) -> Option<&'static str> {
-        match self {
-            CLIAgent::ClaudeCode => Some("ANTHROPIC_API_KEY"),
-            CLIAgent::GeminiCLI => None, // Uses OAuth
-            CLIAgent::Codex => Some("OPENAI_API_KEY"),
-            CLIAgent::Qwen => Some("QWEN_API_KEY"),
-            CLIAgent::Iflow => Some("IFLOW_API_KEY"),
-            CLIAgent::Antigravity => Some("ANTIGRAVITY_API_KEY"),
-        }
-    }
-
-    /// Generate the shell configuration for this agent
-    pub fn generate_config(&self, shell: ShellType, port: u16, api_key: Option<&str>) -> String {
-        let base_url = format!("http://127.0.0.1:{}/v1", port);
-
-        match shell {
-            ShellType::Zsh | ShellType::Bash => {
-                let mut config = format!("export {}=\"{}\"\n", self.env_var_name(), base_url);
-                if let (Some(key_var), Some(key)) = (self.api_key_env_var(), api_key) {
-                    config.push_str(&format!("export {}=\"{}\"\n", key_var, key));
-                }
-                config
-            }
-            ShellType::Fish => {
-                let mut config = format!("set -gx {} \"{}\"\n", self.env_var_name(), base_url);
-                if let (Some(key_var), Some(key)) = (self.api_key_env_var(), api_key) {
-                    config.push_str(&format!("set -gx {} \"{}\"\n", key_var, key));
-                }
-                config
-            }
-            ShellType::Powershell => {
-                let mut config = format!("$env:{} = \"{}\"\n", self.env_var_name(), base_url);
-                if let (Some(key_var), Some(key)) = (self.api_key_env_var(), api_key) {
-                    config.push_str(&format!("$env:{} = \"{}\"\n", key_var, key));
-                }
-                config
-            }
-            ShellType::Cmd => {
-                let mut config = format!("set {}={}\n", self.env_var_name(), base_url);
-                if let (Some(key_var), Some(key)) = (self.api_key_env_var(), api_key) {
-                    config.push_str(&format!("set {}={}\n", key_var, key));
-                }
-                config
-            }
-        }
-    }
-}
-
 #[derive(Error, Debug, Serialize)]
 pub enum ShellProfileError {
     #[error("Failed to read profile: {0}")]
@@ -196,10 +111,24 @@ pub fn get_profile_path(shell: ShellType) -> PathBuf {
     shell.profile_path()
 }
 
-/// Check if an agent is configured in the profile
-pub fn is_configured_in_profile(shell: ShellType, agent: CLIAgent) -> bool {
+/// Parse a shell name (as typed by a user, e.g. on the `zest` CLI or in a
+/// settings field) into a [`ShellType`].
+pub fn parse_shell_type(shell: &str) -> Result<ShellType, String> {
+    match shell.to_lowercase().as_str() {
+        "zsh" => Ok(ShellType::Zsh),
+        "bash" => Ok(ShellType::Bash),
+        "fish" => Ok(ShellType::Fish),
+        "powershell" | "pwsh" => Ok(ShellType::Powershell),
+        "cmd" => Ok(ShellType::Cmd),
+        _ => Err(format!("Unknown shell type: {}", shell)),
+    }
+}
+
+/// Check whether a named configuration block (identified by display name,
+/// e.g. an `AgentManifest::name`) is present in the profile.
+pub fn is_configured(shell: ShellType, marker_name: &str) -> bool {
     let profile_path = shell.profile_path();
-    let marker = format!("# Zest Configuration for {}", agent.display_name());
+    let marker = format!("# Zest Configuration for {}", marker_name);
 
     match std::fs::read_to_string(&profile_path) {
         Ok(content) => content.contains(&marker),
@@ -207,16 +136,12 @@ pub fn is_configured_in_profile(shell: ShellType, agent: CLIAgent) -> bool {
     }
 }
 
-/// Add configuration to shell profile
-pub fn add_to_profile(
-    shell: ShellType,
-    agent: CLIAgent,
-    port: u16,
-    api_key: Option<&str>,
-) -> Result<(), ShellProfileError> {
+/// Insert (replacing any existing block with the same marker) a named
+/// configuration block into the shell profile.
+pub fn add_to_profile(shell: ShellType, marker_name: &str, config: &str) -> Result<(), ShellProfileError> {
     let profile_path = shell.profile_path();
-    let marker = format!("# Zest Configuration for {}", agent.display_name());
-    let end_marker = format!("# End Zest Configuration for {}", agent.display_name());
+    let marker = format!("# Zest Configuration for {}", marker_name);
+    let end_marker = format!("# End Zest Configuration for {}", marker_name);
 
     // Ensure parent directory exists
     if let Some(parent) = profile_path.parent() {
@@ -232,26 +157,10 @@ pub fn add_to_profile(
         String::new()
     };
 
-    // Remove existing configuration if present
-    if let (Some(start), Some(end)) = (content.find(&marker), content.find(&end_marker)) {
-        let end_pos = content[end..].find('\n').map(|p| end + p + 1).unwrap_or(content.len());
-        // Also remove leading newline if present
-        let start_pos = if start > 0 && content.as_bytes()[start - 1] == b'\n' {
-            start - 1
-        } else {
-            start
-        };
-        content.replace_range(start_pos..end_pos, "");
-    }
-
-    // Generate new configuration
-    let config = agent.generate_config(shell, port, api_key);
+    strip_block(&mut content, &marker, &end_marker);
 
     // Append new configuration
-    let new_config = format!(
-        "\n{}\n{}{}\n",
-        marker, config, end_marker
-    );
+    let new_config = format!("\n{}\n{}{}\n", marker, config, end_marker);
     content.push_str(&new_config);
 
     // Write back
@@ -261,11 +170,11 @@ pub fn add_to_profile(
     Ok(())
 }
 
-/// Remove configuration from shell profile
-pub fn remove_from_profile(shell: ShellType, agent: CLIAgent) -> Result<(), ShellProfileError> {
+/// Remove a named configuration block from the shell profile.
+pub fn remove_from_profile(shell: ShellType, marker_name: &str) -> Result<(), ShellProfileError> {
     let profile_path = shell.profile_path();
-    let marker = format!("# Zest Configuration for {}", agent.display_name());
-    let end_marker = format!("# End Zest Configuration for {}", agent.display_name());
+    let marker = format!("# Zest Configuration for {}", marker_name);
+    let end_marker = format!("# End Zest Configuration for {}", marker_name);
 
     if !profile_path.exists() {
         return Ok(());
@@ -274,7 +183,18 @@ pub fn remove_from_profile(shell: ShellType, agent: CLIAgent) -> Result<(), Shel
     let mut content = std::fs::read_to_string(&profile_path)
         .map_err(|e| ShellProfileError::ReadError(e.to_string()))?;
 
-    if let (Some(start), Some(end)) = (content.find(&marker), content.find(&end_marker)) {
+    if strip_block(&mut content, &marker, &end_marker) {
+        std::fs::write(&profile_path, content)
+            .map_err(|e| ShellProfileError::WriteError(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Remove the `marker`..`end_marker` block (if present) from `content` in
+/// place, returning whether anything was removed.
+fn strip_block(content: &mut String, marker: &str, end_marker: &str) -> bool {
+    if let (Some(start), Some(end)) = (content.find(marker), content.find(end_marker)) {
         let end_pos = content[end..].find('\n').map(|p| end + p + 1).unwrap_or(content.len());
         // Also remove leading newline if present
         let start_pos = if start > 0 && content.as_bytes()[start - 1] == b'\n' {
@@ -283,12 +203,10 @@ pub fn remove_from_profile(shell: ShellType, agent: CLIAgent) -> Result<(), Shel
             start
         };
         content.replace_range(start_pos..end_pos, "");
-
-        std::fs::write(&profile_path, content)
-            .map_err(|e| ShellProfileError::WriteError(e.to_string()))?;
+        true
+    } else {
+        false
     }
-
-    Ok(())
 }
 
 /// Create a backup of the shell profile
@@ -316,89 +234,75 @@ pub fn create_backup(shell: ShellType) -> Result<PathBuf, ShellProfileError> {
     Ok(backup_path)
 }
 
-/// Get the environment setup command for display (copy-paste)
-pub fn get_env_command(agent: CLIAgent, port: u16, api_key: Option<&str>) -> String {
-    let shell = detect_shell();
-    let base_url = format!("http://127.0.0.1:{}/v1", port);
-
-    match shell {
-        ShellType::Zsh | ShellType::Bash => {
-            let mut cmd = format!("export {}=\"{}\"", agent.env_var_name(), base_url);
-            if let (Some(key_var), Some(key)) = (agent.api_key_env_var(), api_key) {
-                cmd.push_str(&format!(" && export {}=\"{}\"", key_var, key));
-            }
-            cmd
-        }
-        ShellType::Fish => {
-            let mut cmd = format!("set -gx {} \"{}\"", agent.env_var_name(), base_url);
-            if let (Some(key_var), Some(key)) = (agent.api_key_env_var(), api_key) {
-                cmd.push_str(&format!("; set -gx {} \"{}\"", key_var, key));
-            }
-            cmd
-        }
-        ShellType::Powershell => {
-            let mut cmd = format!("$env:{} = \"{}\"", agent.env_var_name(), base_url);
-            if let (Some(key_var), Some(key)) = (agent.api_key_env_var(), api_key) {
-                cmd.push_str(&format!("; $env:{} = \"{}\"", key_var, key));
-            }
-            cmd
-        }
-        ShellType::Cmd => {
-            let mut cmd = format!("set {}={}", agent.env_var_name(), base_url);
-            if let (Some(key_var), Some(key)) = (agent.api_key_env_var(), api_key) {
-                cmd.push_str(&format!(" & set {}={}", key_var, key));
-            }
-            cmd
-        }
-    }
-}
-
-/// Windows-specific: Set environment variable in user registry
+/// Windows-specific: set an environment variable directly in
+/// `HKCU\Environment` and broadcast the change.
+///
+/// `setx` (the previous approach) silently truncates values over 1024
+/// characters and doesn't notify anything already running, so new shells
+/// wouldn't see the change until the next logout. Writing the registry value
+/// ourselves removes the length limit, and broadcasting `WM_SETTINGCHANGE`
+/// tells Explorer and newly-spawned processes to pick it up immediately.
 #[cfg(windows)]
 pub fn set_windows_env_var(name: &str, value: &str) -> Result<(), ShellProfileError> {
-    use std::process::Command;
+    use winreg::enums::{HKEY_CURRENT_USER, KEY_WRITE};
+    use winreg::RegKey;
 
-    let result = Command::new("setx")
-        .args([name, value])
-        .output()
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let env = hkcu
+        .open_subkey_with_flags("Environment", KEY_WRITE)
+        .map_err(|e| ShellProfileError::WriteError(e.to_string()))?;
+    env.set_value(name, &value)
         .map_err(|e| ShellProfileError::WriteError(e.to_string()))?;
 
-    if !result.status.success() {
-        return Err(ShellProfileError::WriteError(
-            String::from_utf8_lossy(&result.stderr).to_string()
-        ));
-    }
-
+    broadcast_environment_change();
     Ok(())
 }
 
-/// Windows-specific: Remove environment variable from user registry
+/// Windows-specific: remove an environment variable from `HKCU\Environment`
+/// and broadcast the change. See [`set_windows_env_var`].
 #[cfg(windows)]
 pub fn remove_windows_env_var(name: &str) -> Result<(), ShellProfileError> {
-    use std::process::Command;
-
-    let result = Command::new("reg")
-        .args([
-            "delete",
-            "HKCU\\Environment",
-            "/v",
-            name,
-            "/f",
-        ])
-        .output()
+    use winreg::enums::{HKEY_CURRENT_USER, KEY_WRITE};
+    use winreg::RegKey;
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let env = hkcu
+        .open_subkey_with_flags("Environment", KEY_WRITE)
         .map_err(|e| ShellProfileError::WriteError(e.to_string()))?;
 
-    // Ignore errors if variable doesn't exist
-    if !result.status.success() {
-        let stderr = String::from_utf8_lossy(&result.stderr);
-        if !stderr.contains("unable to find") && !stderr.contains("não foi possível") {
-            return Err(ShellProfileError::WriteError(stderr.to_string()));
-        }
+    match env.delete_value(name) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(ShellProfileError::WriteError(e.to_string())),
     }
 
+    broadcast_environment_change();
     Ok(())
 }
 
+/// Broadcast `WM_SETTINGCHANGE` with an `"Environment"` lParam so Explorer
+/// and new processes notice a `HKCU\Environment` change without a logout.
+#[cfg(windows)]
+fn broadcast_environment_change() {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE,
+    };
+
+    let param: Vec<u16> = "Environment".encode_utf16().chain(std::iter::once(0)).collect();
+    let mut result: usize = 0;
+    unsafe {
+        SendMessageTimeoutW(
+            HWND_BROADCAST,
+            WM_SETTINGCHANGE,
+            0,
+            param.as_ptr() as isize,
+            SMTO_ABORTIFHUNG,
+            5000,
+            &mut result,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -414,21 +318,14 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_config_bash() {
-        let config = CLIAgent::ClaudeCode.generate_config(ShellType::Bash, 8317, Some("test-key"));
-        assert!(config.contains("export ANTHROPIC_BASE_URL=\"http://127.0.0.1:8317/v1\""));
-        assert!(config.contains("export ANTHROPIC_API_KEY=\"test-key\""));
-    }
-
-    #[test]
-    fn test_generate_config_fish() {
-        let config = CLIAgent::ClaudeCode.generate_config(ShellType::Fish, 8317, None);
-        assert!(config.contains("set -gx ANTHROPIC_BASE_URL"));
-    }
-
-    #[test]
-    fn test_generate_config_powershell() {
-        let config = CLIAgent::ClaudeCode.generate_config(ShellType::Powershell, 8317, None);
-        assert!(config.contains("$env:ANTHROPIC_BASE_URL"));
+    fn test_strip_block_removes_marked_section_only() {
+        let mut content = "# existing content\n\n# Zest Configuration for Test Agent\nexport FOO=\"bar\"\n# End Zest Configuration for Test Agent\n".to_string();
+        assert!(strip_block(
+            &mut content,
+            "# Zest Configuration for Test Agent",
+            "# End Zest Configuration for Test Agent"
+        ));
+        assert!(!content.contains("FOO"));
+        assert!(content.contains("# existing content"));
     }
 }
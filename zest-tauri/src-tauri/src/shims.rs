@@ -0,0 +1,150 @@
+//! Proxy-injecting shim scripts — an alternative to editing shell profiles.
+//!
+//! `configure_agent_advanced`'s "shell" storage option works by mutating
+//! the user's `.bashrc`/`.zshrc`/etc., which applies to every shell the
+//! user opens, for every agent configured that way. This writes one small
+//! executable wrapper per agent binary into a Zest-managed directory
+//! instead (`~/.zest/shims`): each shim exports the proxy env vars and
+//! `exec`s the real binary resolved by `commands::find_agent_binary`, so
+//! proxying is opt-in per-agent and per-shell-session (just put the shim
+//! directory on `PATH` ahead of the real binary, or invoke it directly)
+//! without touching any dotfile. Every shim written is tracked in a small
+//! registry file alongside them so they can be listed and revoked later.
+
+use crate::agent_registry::AgentManifest;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ShimError {
+    #[error("failed to write shim: {0}")]
+    WriteError(String),
+}
+
+/// One shim script Zest has created for an agent's binary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShimRecord {
+    pub agent_id: String,
+    pub binary_name: String,
+    pub shim_path: String,
+    pub real_binary_path: String,
+}
+
+fn registry_path() -> PathBuf {
+    crate::paths::shim_dir().join("shims.json")
+}
+
+fn load_registry() -> Vec<ShimRecord> {
+    let Ok(content) = fs::read_to_string(registry_path()) else { return Vec::new() };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+fn save_registry(records: &[ShimRecord]) -> Result<(), ShimError> {
+    let content = serde_json::to_string_pretty(records).map_err(|e| ShimError::WriteError(e.to_string()))?;
+    fs::write(registry_path(), content).map_err(|e| ShimError::WriteError(e.to_string()))
+}
+
+/// Write a shim for every one of `manifest`'s `binary_names` (falling back
+/// to `manifest.id` when empty), each exporting `env_vars` before
+/// `exec`ing `real_binary_path`. Replaces any shims previously created for
+/// this agent.
+pub fn create_shims(
+    manifest: &AgentManifest,
+    real_binary_path: &str,
+    env_vars: &[(String, String)],
+) -> Result<Vec<ShimRecord>, ShimError> {
+    let dir = crate::paths::shim_dir();
+    fs::create_dir_all(&dir).map_err(|e| ShimError::WriteError(e.to_string()))?;
+
+    let binary_names = if manifest.binary_names.is_empty() {
+        vec![manifest.id.clone()]
+    } else {
+        manifest.binary_names.clone()
+    };
+
+    let mut records: Vec<ShimRecord> = load_registry().into_iter().filter(|r| r.agent_id != manifest.id).collect();
+
+    let mut created = Vec::new();
+    for binary_name in binary_names {
+        let shim_path = write_shim(&dir, &binary_name, real_binary_path, env_vars)?;
+        let record = ShimRecord {
+            agent_id: manifest.id.clone(),
+            binary_name,
+            shim_path: shim_path.display().to_string(),
+            real_binary_path: real_binary_path.to_string(),
+        };
+        records.push(record.clone());
+        created.push(record);
+    }
+
+    save_registry(&records)?;
+    Ok(created)
+}
+
+#[cfg(unix)]
+fn write_shim(
+    dir: &std::path::Path,
+    binary_name: &str,
+    real_binary_path: &str,
+    env_vars: &[(String, String)],
+) -> Result<PathBuf, ShimError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut script = String::from("#!/bin/sh\n");
+    for (key, value) in env_vars {
+        script.push_str(&format!("export {}=\"{}\"\n", key, value));
+    }
+    script.push_str(&format!("exec \"{}\" \"$@\"\n", real_binary_path));
+
+    let path = dir.join(binary_name);
+    fs::write(&path, script).map_err(|e| ShimError::WriteError(e.to_string()))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+        .map_err(|e| ShimError::WriteError(e.to_string()))?;
+    Ok(path)
+}
+
+#[cfg(windows)]
+fn write_shim(
+    dir: &std::path::Path,
+    binary_name: &str,
+    real_binary_path: &str,
+    env_vars: &[(String, String)],
+) -> Result<PathBuf, ShimError> {
+    let mut script = String::from("@echo off\n");
+    for (key, value) in env_vars {
+        script.push_str(&format!("set {}={}\n", key, value));
+    }
+    script.push_str(&format!("\"{}\" %*\n", real_binary_path));
+
+    let path = dir.join(format!("{}.cmd", binary_name));
+    fs::write(&path, script).map_err(|e| ShimError::WriteError(e.to_string()))?;
+    Ok(path)
+}
+
+/// Every shim Zest has created, across all agents.
+pub fn list_shims() -> Vec<ShimRecord> {
+    load_registry()
+}
+
+/// Remove every shim created for `agent_id`.
+pub fn remove_shims(agent_id: &str) -> Result<(), ShimError> {
+    let records = load_registry();
+    let (to_remove, remaining): (Vec<_>, Vec<_>) =
+        records.into_iter().partition(|r| r.agent_id == agent_id);
+    for record in &to_remove {
+        let _ = fs::remove_file(&record.shim_path);
+    }
+    save_registry(&remaining)
+}
+
+/// Remove the entire shim directory and its registry, e.g. on "default"
+/// teardown.
+pub fn remove_all_shims() -> Result<(), ShimError> {
+    let dir = crate::paths::shim_dir();
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| ShimError::WriteError(e.to_string()))?;
+    }
+    Ok(())
+}
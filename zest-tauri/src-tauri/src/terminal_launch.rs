@@ -0,0 +1,261 @@
+//! Launch a binary inside a terminal window with env vars pre-set.
+//!
+//! `find_agent_binary` locates the CLI and `get_env_command` builds the
+//! export line, but the user still has to open a terminal and paste it.
+//! This runs the binary directly, with the proxy env vars already set, in
+//! whatever terminal is appropriate for the platform:
+//!
+//! - macOS: `Terminal.app`, driven through `osascript`.
+//! - Windows: Windows Terminal (`wt.exe`) if installed, else a plain `cmd`
+//!   window. The launcher process itself runs with `CREATE_NO_WINDOW` so no
+//!   extra console flashes up before the real terminal opens.
+//! - Linux: the user's configured terminal emulator, discovered the same
+//!   way a file manager's "Open in Terminal" would — the
+//!   `x-terminal-emulator` alternatives symlink, then the freedesktop
+//!   `.desktop` application database — rather than guessing
+//!   gnome-terminal/konsole/xterm and falling over on anything else.
+
+use crate::agent_registry::AgentManifest;
+use std::ffi::OsString;
+use std::process::Command;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TerminalLaunchError {
+    #[error("could not find a terminal emulator to launch")]
+    NoTerminalFound,
+    #[error("failed to launch terminal: {0}")]
+    SpawnFailed(String),
+}
+
+/// Launch `binary_path` inside a terminal window with `env_vars` set first.
+pub fn launch(binary_path: &str, env_vars: &[(String, String)]) -> Result<(), TerminalLaunchError> {
+    #[cfg(target_os = "macos")]
+    {
+        launch_macos(binary_path, env_vars)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        launch_windows(binary_path, env_vars)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        launch_linux(binary_path, env_vars)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = (binary_path, env_vars);
+        Err(TerminalLaunchError::NoTerminalFound)
+    }
+}
+
+/// Run `command` as a child process with `manifest`'s proxy env vars set,
+/// inheriting stdio so interactive agents keep working. Returns the child's
+/// exit code. Unlike [`launch`], this never opens a terminal window and
+/// never touches a shell profile — a zero-footprint way to point a single
+/// invocation at the proxy.
+pub fn spawn_with_env(
+    manifest: &AgentManifest,
+    port: u16,
+    api_key: Option<&str>,
+    command: &[OsString],
+) -> Result<i32, TerminalLaunchError> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| TerminalLaunchError::SpawnFailed("no command given".to_string()))?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    cmd.env(&manifest.env_var, manifest.base_url(port));
+    if let (Some(key_var), Some(key)) = (&manifest.api_key_env_var, api_key) {
+        cmd.env(key_var, key);
+    }
+
+    let status = cmd
+        .status()
+        .map_err(|e| TerminalLaunchError::SpawnFailed(e.to_string()))?;
+
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Build a `sh`/`cmd`-flavored one-liner that exports `env_vars` and then
+/// runs `binary_path`.
+fn shell_script(binary_path: &str, env_vars: &[(String, String)], windows_style: bool) -> String {
+    let mut script = String::new();
+    for (key, value) in env_vars {
+        if windows_style {
+            script.push_str(&format!("set {}={} && ", key, value));
+        } else {
+            script.push_str(&format!("export {}=\"{}\"; ", key, value));
+        }
+    }
+    script.push_str(binary_path);
+    script
+}
+
+#[cfg(target_os = "macos")]
+fn launch_macos(binary_path: &str, env_vars: &[(String, String)]) -> Result<(), TerminalLaunchError> {
+    let script = shell_script(binary_path, env_vars, false);
+    // Escape for embedding inside an AppleScript string literal.
+    let escaped = script.replace('\\', "\\\\").replace('"', "\\\"");
+    let apple_script = format!("tell application \"Terminal\" to do script \"{}\"", escaped);
+
+    Command::new("osascript")
+        .args(["-e", &apple_script])
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| TerminalLaunchError::SpawnFailed(e.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn launch_windows(binary_path: &str, env_vars: &[(String, String)]) -> Result<(), TerminalLaunchError> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let script = shell_script(binary_path, env_vars, true);
+
+    let has_windows_terminal = Command::new("where")
+        .arg("wt.exe")
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+
+    if has_windows_terminal {
+        return Command::new("wt.exe")
+            .args(["cmd", "/K", &script])
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| TerminalLaunchError::SpawnFailed(e.to_string()));
+    }
+
+    Command::new("cmd")
+        .args(["/C", "start", "cmd", "/K", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| TerminalLaunchError::SpawnFailed(e.to_string()))
+}
+
+#[cfg(target_os = "linux")]
+fn launch_linux(binary_path: &str, env_vars: &[(String, String)]) -> Result<(), TerminalLaunchError> {
+    let script = shell_script(binary_path, env_vars, false);
+    let terminal = find_terminal_emulator().ok_or(TerminalLaunchError::NoTerminalFound)?;
+
+    let inner_command = format!("{}; exec $SHELL", script);
+    shell_words_split(&terminal)
+        .split_first()
+        .ok_or(TerminalLaunchError::NoTerminalFound)
+        .and_then(|(program, args)| {
+            Command::new(program)
+                .args(args)
+                .args(["-e", "sh", "-c", &inner_command])
+                .spawn()
+                .map(|_| ())
+                .map_err(|e| TerminalLaunchError::SpawnFailed(e.to_string()))
+        })
+}
+
+/// Split a shell-style command line on whitespace. `.desktop` `Exec=` lines
+/// are simple enough (no quoting once field codes are stripped) that this
+/// is sufficient without pulling in a full shell-word parser.
+#[cfg(target_os = "linux")]
+fn shell_words_split(command: &str) -> Vec<String> {
+    command.split_whitespace().map(|s| s.to_string()).collect()
+}
+
+/// Find the user's terminal emulator the way a file manager would: the
+/// Debian/Ubuntu `x-terminal-emulator` alternatives symlink, a `$TERMINAL`
+/// override, then the freedesktop `.desktop` application database.
+#[cfg(target_os = "linux")]
+fn find_terminal_emulator() -> Option<String> {
+    if let Ok(target) = std::fs::read_link("/etc/alternatives/x-terminal-emulator") {
+        if let Some(name) = target.file_name().and_then(|n| n.to_str()) {
+            return Some(name.to_string());
+        }
+    }
+
+    if let Ok(term) = std::env::var("TERMINAL") {
+        if !term.is_empty() {
+            return Some(term);
+        }
+    }
+
+    let mut search_dirs = Vec::new();
+    if let Some(data_home) = dirs::data_dir() {
+        search_dirs.push(data_home.join("applications"));
+    }
+    search_dirs.push(std::path::PathBuf::from("/usr/local/share/applications"));
+    search_dirs.push(std::path::PathBuf::from("/usr/share/applications"));
+
+    for dir in search_dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("desktop") {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+            if !is_terminal_emulator_entry(&content) {
+                continue;
+            }
+            if let Some(exec) = parse_exec_line(&content) {
+                return Some(exec);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn is_terminal_emulator_entry(desktop_file: &str) -> bool {
+    desktop_file.lines().any(|line| {
+        let line = line.trim_start();
+        (line.starts_with("Categories=") && line.contains("TerminalEmulator"))
+            || line == "Terminal=true"
+    })
+}
+
+/// Pull the command out of a `.desktop` file's `Exec=` line, stripping
+/// freedesktop field codes (`%f`, `%F`, `%u`, `%U`, ...) since we're
+/// launching a fixed command rather than opening a file or URL.
+#[cfg(target_os = "linux")]
+fn parse_exec_line(desktop_file: &str) -> Option<String> {
+    let exec_line = desktop_file.lines().find(|l| l.trim_start().starts_with("Exec="))?;
+    let raw = exec_line.splitn(2, '=').nth(1)?.trim();
+    let command = raw
+        .split_whitespace()
+        .filter(|token| !token.starts_with('%'))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if command.is_empty() {
+        None
+    } else {
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exec_line_strips_field_codes() {
+        let desktop_file = "[Desktop Entry]\nExec=gnome-terminal %F\nTerminal=false\n";
+        assert_eq!(parse_exec_line(desktop_file), Some("gnome-terminal".to_string()));
+    }
+
+    #[test]
+    fn test_is_terminal_emulator_entry_matches_category() {
+        let desktop_file = "[Desktop Entry]\nCategories=System;TerminalEmulator;\n";
+        assert!(is_terminal_emulator_entry(desktop_file));
+
+        let non_terminal = "[Desktop Entry]\nCategories=Utility;\n";
+        assert!(!is_terminal_emulator_entry(non_terminal));
+    }
+}
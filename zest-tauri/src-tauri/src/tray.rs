@@ -1,17 +1,32 @@
 //! System tray management
 //!
-//! Handles the system tray icon and menu for Zest.
+//! Handles the system tray icon and menu for Zest: a status header, a
+//! start/stop toggle for the proxy, a per-agent submenu for toggling shell
+//! profile configuration, and a quit item.
 
 use tauri::{
+    menu::{CheckMenuItemBuilder, Menu, MenuBuilder, MenuEvent, MenuItemBuilder, SubmenuBuilder},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager, Runtime,
+    AppHandle, Manager, Runtime,
 };
 
+use crate::agent_registry;
+use crate::proxy::{self, ProxyState};
+use crate::shell_profile;
+
+const MENU_ID_TOGGLE_PROXY: &str = "toggle-proxy";
+const MENU_ID_QUIT: &str = "quit";
+const AGENT_MENU_PREFIX: &str = "agent:";
+
 /// Setup the system tray
 pub fn setup_tray<R: Runtime>(app: &tauri::App<R>) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_tray_menu(app.handle(), false, 0)?;
+
     let _tray = TrayIconBuilder::with_id("main-tray")
-        .tooltip("Zest - AI Quota Manager")
-        .icon(app.default_window_icon().unwrap().clone())
+        .tooltip("Zest - Proxy stopped")
+        .icon(tray_icon(false))
+        .menu(&menu)
+        .show_menu_on_left_click(false)
         .on_tray_icon_event(|tray, event| {
             if let TrayIconEvent::Click {
                 button: MouseButton::Left,
@@ -27,7 +42,7 @@ pub fn setup_tray<R: Runtime>(app: &tauri::App<R>) -> Result<(), Box<dyn std::er
                 }
             }
         })
-        .show_menu_on_left_click(false)
+        .on_menu_event(handle_menu_event)
         .build(app)?;
 
     log::info!("System tray initialized");
@@ -35,7 +50,140 @@ pub fn setup_tray<R: Runtime>(app: &tauri::App<R>) -> Result<(), Box<dyn std::er
     Ok(())
 }
 
-/// Update tray tooltip with current status
+/// Build (or rebuild) the tray's context menu from current proxy/profile state.
+fn build_tray_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    running: bool,
+    port: u16,
+) -> tauri::Result<Menu<R>> {
+    let status_label = if running {
+        format!("Proxy: Running on port {}", port)
+    } else {
+        "Proxy: Stopped".to_string()
+    };
+    let status_item = MenuItemBuilder::with_id("status", status_label)
+        .enabled(false)
+        .build(app)?;
+
+    let toggle_item = MenuItemBuilder::with_id(
+        MENU_ID_TOGGLE_PROXY,
+        if running { "Stop Proxy" } else { "Start Proxy" },
+    )
+    .build(app)?;
+
+    let shell = shell_profile::detect_shell();
+    let mut agents_submenu = SubmenuBuilder::new(app, "Agents");
+    for manifest in agent_registry::all_manifests() {
+        let checked = shell_profile::is_configured(shell, &manifest.name);
+        let item = CheckMenuItemBuilder::with_id(
+            format!("{}{}", AGENT_MENU_PREFIX, manifest.id),
+            &manifest.name,
+        )
+        .checked(checked)
+        .build(app)?;
+        agents_submenu = agents_submenu.item(&item);
+    }
+    let agents_submenu = agents_submenu.build()?;
+
+    let quit_item = MenuItemBuilder::with_id(MENU_ID_QUIT, "Quit").build(app)?;
+
+    MenuBuilder::new(app)
+        .item(&status_item)
+        .separator()
+        .item(&toggle_item)
+        .separator()
+        .item(&agents_submenu)
+        .separator()
+        .item(&quit_item)
+        .build()
+}
+
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    let id = event.id().0.as_str();
+
+    if id == MENU_ID_QUIT {
+        app.exit(0);
+        return;
+    }
+
+    if id == MENU_ID_TOGGLE_PROXY {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<ProxyState>();
+            let running = state.inner.lock().await.status.running;
+            let result = if running {
+                proxy::stop_proxy(&state.inner).await
+            } else {
+                let settings_state = app.state::<crate::settings::SettingsState>();
+                let (settings_proxy, ca_bundle_path) = {
+                    let settings = settings_state.inner.lock().await;
+                    (settings.outbound_proxy_url.clone(), settings.ca_bundle_path.clone())
+                };
+                let outbound_proxy = proxy::resolve_outbound_proxy(settings_proxy.as_deref());
+                proxy::start_proxy(
+                    &app,
+                    &state.inner,
+                    outbound_proxy.as_deref(),
+                    ca_bundle_path.as_deref().map(std::path::Path::new),
+                )
+                .await
+            };
+            match result {
+                Ok(status) => {
+                    if let Err(e) = rebuild_tray_menu(&app, status.running, status.port) {
+                        log::warn!("failed to rebuild tray menu: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("failed to toggle proxy from tray: {}", e),
+            }
+        });
+        return;
+    }
+
+    if let Some(agent_id) = id.strip_prefix(AGENT_MENU_PREFIX) {
+        let app = app.clone();
+        let agent_id = agent_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = toggle_agent(&app, &agent_id).await {
+                log::warn!("failed to toggle agent '{}' from tray: {}", agent_id, e);
+            }
+        });
+    }
+}
+
+async fn toggle_agent<R: Runtime>(app: &AppHandle<R>, agent_id: &str) -> Result<(), String> {
+    let manifest = agent_registry::find(agent_id).map_err(|e| e.to_string())?;
+    let shell = shell_profile::detect_shell();
+
+    if shell_profile::is_configured(shell, &manifest.name) {
+        shell_profile::remove_from_profile(shell, &manifest.name).map_err(|e| e.to_string())?;
+    } else {
+        let state = app.state::<ProxyState>();
+        let port = state.inner.lock().await.status.port;
+        let config = manifest.render_profile_config(shell, port, None);
+        shell_profile::add_to_profile(shell, &manifest.name, &config).map_err(|e| e.to_string())?;
+    }
+
+    let state = app.state::<ProxyState>();
+    let status = state.inner.lock().await.status.clone();
+    rebuild_tray_menu(app, status.running, status.port).map_err(|e| e.to_string())
+}
+
+/// Rebuild the tray menu and refresh the tooltip/icon. Call whenever proxy
+/// state or agent profile configuration changes.
+pub fn rebuild_tray_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    running: bool,
+    port: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_tray_menu(app, running, port)?;
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        tray.set_menu(Some(menu))?;
+    }
+    update_tray_tooltip(app, running, port)
+}
+
+/// Update tray tooltip and icon with current status
 pub fn update_tray_tooltip<R: Runtime>(
     app: &tauri::AppHandle<R>,
     running: bool,
@@ -48,6 +196,15 @@ pub fn update_tray_tooltip<R: Runtime>(
             "Zest - Proxy stopped".to_string()
         };
         tray.set_tooltip(Some(&tooltip))?;
+        tray.set_icon(Some(tray_icon(running)))?;
     }
     Ok(())
 }
+
+fn tray_icon(running: bool) -> tauri::image::Image<'static> {
+    if running {
+        tauri::include_image!("icons/tray-active.png")
+    } else {
+        tauri::include_image!("icons/tray-inactive.png")
+    }
+}
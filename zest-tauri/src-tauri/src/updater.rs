@@ -0,0 +1,361 @@
+//! Self-updater for the Zest app itself.
+//!
+//! Fetches the latest release of this app's own GitHub repository — distinct
+//! from `proxy::GITHUB_REPO`, which is the external CLIProxyAPI binary this
+//! app manages — compares its `tag_name` against [`CLIENT_VERSION`] with
+//! semver, and (once the user opts in from the About page) downloads and
+//! swaps in the matching platform asset. Reuses the same checksum-verify
+//! shape as `install_jobs`, just against the app binary instead of the
+//! proxy's, plus an extra layer a compromised release host can't forge: a
+//! detached Ed25519 signature over the download, checked against a trusted
+//! public key embedded in this binary.
+
+use crate::models::{GitHubAsset, GitHubRelease};
+use crate::proxy::CLIENT_VERSION;
+use base64::Engine;
+use semver::Version;
+use sha2::{Digest, Sha256};
+use sigh::{Algorithm, Key, PublicKey};
+use thiserror::Error;
+
+/// GitHub repository for Zest app releases.
+const APP_GITHUB_REPO: &str = "heliab125/zest";
+
+/// Public half of the key releases are signed with. Ships inside the app so
+/// a compromised release host (or a tampered-with download in transit)
+/// can't be trusted just because its checksum happens to match a
+/// checksums.txt hosted alongside it - the detached signature has to verify
+/// against this key too. The matching private key lives outside this repo,
+/// held by whoever cuts releases.
+const TRUSTED_RELEASE_PUBLIC_KEY_PEM: &str = "-----BEGIN PUBLIC KEY-----\n\
+MCowBQYDK2VwAyEAhUfyaXWWHWUYTMT8PT2rB7fE/8yM4q8DVWu8NZaT0Ck=\n\
+-----END PUBLIC KEY-----\n";
+
+#[derive(Error, Debug)]
+pub enum UpdaterError {
+    #[error("network error: {0}")]
+    Network(String),
+    #[error("could not parse version: {0}")]
+    InvalidVersion(String),
+    #[error("no compatible release asset for this platform")]
+    NoCompatibleAsset,
+    #[error("checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("detached signature is missing or does not verify against the trusted release key")]
+    SignatureInvalid,
+    #[error("failed to swap binary: {0}")]
+    SwapFailed(String),
+}
+
+/// Fetch the latest release and return it if its `tag_name` is a newer
+/// semver version than the running [`CLIENT_VERSION`]. Pre-release tags
+/// (e.g. `1.2.0-beta.1`) compare the way semver defines: older than their
+/// own stable release, so a pre-release build is never silently offered as
+/// an upgrade over a later stable one.
+pub async fn check_for_update() -> Result<Option<GitHubRelease>, UpdaterError> {
+    let client = reqwest::Client::new();
+    let release: GitHubRelease = client
+        .get(format!("https://api.github.com/repos/{}/releases/latest", APP_GITHUB_REPO))
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "Zest/1.0")
+        .send()
+        .await
+        .map_err(|e| UpdaterError::Network(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| UpdaterError::Network(e.to_string()))?;
+
+    let current = Version::parse(CLIENT_VERSION).map_err(|e| UpdaterError::InvalidVersion(e.to_string()))?;
+    let latest_tag = release.tag_name.strip_prefix('v').unwrap_or(&release.tag_name);
+    let latest = Version::parse(latest_tag).map_err(|e| UpdaterError::InvalidVersion(e.to_string()))?;
+
+    Ok((latest > current).then_some(release))
+}
+
+/// Current build's Rust target triple, used to pick the matching asset out
+/// of a release (e.g. `x86_64-unknown-linux-gnu`, `aarch64-apple-darwin`).
+fn current_target_triple() -> &'static str {
+    if cfg!(all(target_os = "windows", target_arch = "aarch64")) {
+        "aarch64-pc-windows-msvc"
+    } else if cfg!(target_os = "windows") {
+        "x86_64-pc-windows-msvc"
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        "aarch64-apple-darwin"
+    } else if cfg!(target_os = "macos") {
+        "x86_64-apple-darwin"
+    } else if cfg!(target_arch = "aarch64") {
+        "aarch64-unknown-linux-gnu"
+    } else {
+        "x86_64-unknown-linux-gnu"
+    }
+}
+
+/// Select the asset matching the running target triple, skipping checksum
+/// files. On Windows, where an asset is sometimes just named `zest.exe`
+/// rather than carrying a full triple, fall back to the lone `.exe` asset.
+pub fn find_compatible_asset(assets: &[GitHubAsset]) -> Option<&GitHubAsset> {
+    let triple = current_target_triple();
+
+    let is_checksum = |name: &str| {
+        let name = name.to_lowercase();
+        name.contains("checksum") || name.ends_with(".sha256")
+    };
+
+    if let Some(asset) = assets.iter().find(|a| !is_checksum(&a.name) && a.name.contains(triple)) {
+        return Some(asset);
+    }
+
+    if cfg!(target_os = "windows") {
+        return assets.iter().find(|a| !is_checksum(&a.name) && a.name.ends_with(".exe"));
+    }
+
+    None
+}
+
+/// [`find_compatible_asset`], wrapped as a [`UpdaterError::NoCompatibleAsset`]
+/// for callers that want to propagate it as an error rather than an
+/// `Option`.
+pub fn select_asset(release: &GitHubRelease) -> Result<&GitHubAsset, UpdaterError> {
+    find_compatible_asset(&release.assets).ok_or(UpdaterError::NoCompatibleAsset)
+}
+
+/// Does `name` (an archive entry's path) look like this app's own
+/// executable? Shared by the archive extraction step below; mirrors
+/// `proxy::is_binary_entry_name`'s "match the file name, ignore the
+/// directory it's nested under" approach.
+fn is_app_binary_entry_name(name: &str) -> bool {
+    let base = std::path::Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    base == "zest" || base == "zest.exe"
+}
+
+/// Download `asset`, verify it against an accompanying checksum asset (the
+/// same "`<hash>  <name>`" or single-hash convention `install_jobs` checks
+/// for the proxy binary) and the release's detached Ed25519 signature over
+/// that checksum, extract the executable if the asset turns out to be an
+/// archive rather than a bare binary, and atomically swap it in for the
+/// running executable. `on_progress` is called with `(downloaded, total)`
+/// as bytes arrive, where `total` is the larger of the response's
+/// `Content-Length` and `asset.size` (or, on a resumed download, the
+/// resume offset plus `Content-Length`), so callers can render a percentage.
+pub async fn apply_update(
+    release: &GitHubRelease,
+    asset: &GitHubAsset,
+    mut on_progress: impl FnMut(u64, u64),
+) -> Result<(), UpdaterError> {
+    let client = reqwest::Client::new();
+    let data = download(&client, asset, &mut on_progress).await?;
+
+    if let Some(expected) = fetch_expected_checksum(&client, &release.assets, &asset.name).await {
+        let actual = sha256_hex(&data);
+        if actual != expected {
+            return Err(UpdaterError::ChecksumMismatch { expected, actual });
+        }
+    }
+
+    let signature = fetch_detached_signature(&client, &release.assets, &asset.name)
+        .await
+        .ok_or(UpdaterError::SignatureInvalid)?;
+    verify_release_signature(&data, &signature)?;
+
+    let (binary_data, _executable) = crate::archive::extract_entry(&data, is_app_binary_entry_name).map_err(|e| {
+        metrics::counter!("zest_extraction_failures_total", "component" => "updater").increment(1);
+        UpdaterError::SwapFailed(e.to_string())
+    })?;
+
+    swap_binary(&binary_data)
+}
+
+/// Find the detached-signature asset for `asset_name` (published alongside
+/// it as `<asset_name>.sig`: a single base64-encoded 64-byte Ed25519
+/// signature) and return its decoded bytes.
+async fn fetch_detached_signature(
+    client: &reqwest::Client,
+    assets: &[GitHubAsset],
+    asset_name: &str,
+) -> Option<Vec<u8>> {
+    let signature_asset_name = format!("{}.sig", asset_name);
+    let signature_asset = assets.iter().find(|a| a.name == signature_asset_name)?;
+
+    let body = client
+        .get(&signature_asset.browser_download_url)
+        .header("User-Agent", "Zest/1.0")
+        .send()
+        .await
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+
+    base64::engine::general_purpose::STANDARD.decode(body.trim()).ok()
+}
+
+/// Verify `signature` (64 raw bytes) against the SHA-256 digest of `data`
+/// using the embedded [`TRUSTED_RELEASE_PUBLIC_KEY_PEM`].
+fn verify_release_signature(data: &[u8], signature: &[u8]) -> Result<(), UpdaterError> {
+    let public_key =
+        PublicKey::from_pem(TRUSTED_RELEASE_PUBLIC_KEY_PEM).map_err(|_| UpdaterError::SignatureInvalid)?;
+    let digest = Sha256::digest(data);
+
+    public_key
+        .verify(&digest, signature, Algorithm::Ed25519)
+        .map_err(|_| UpdaterError::SignatureInvalid)
+}
+
+/// Directory partial self-update downloads are staged in, so an interrupted
+/// transfer can be resumed with a `Range` request instead of restarting.
+fn download_dir() -> std::path::PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("Quotio")
+        .join("updates")
+}
+
+/// Stream `asset` to a `<name>.part` file in [`download_dir`], resuming from
+/// whatever is already on disk with a `Range: bytes=<offset>-` request if a
+/// previous attempt left one behind. Returns the complete file's bytes once
+/// the full length has been received; a connection drop leaves the partial
+/// file in place for the next call to pick up from.
+async fn download(
+    client: &reqwest::Client,
+    asset: &GitHubAsset,
+    on_progress: &mut impl FnMut(u64, u64),
+) -> Result<Vec<u8>, UpdaterError> {
+    use futures_util::StreamExt;
+    use std::io::Write;
+
+    let dir = download_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| UpdaterError::Network(e.to_string()))?;
+    let partial_path = dir.join(format!("{}.part", asset.name));
+
+    let already_downloaded = std::fs::metadata(&partial_path).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(&asset.browser_download_url).header("User-Agent", "Zest/1.0");
+    if already_downloaded > 0 {
+        request = request.header("Range", format!("bytes={}-", already_downloaded));
+    }
+
+    let response = request.send().await.map_err(|e| UpdaterError::Network(e.to_string()))?;
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { already_downloaded } else { 0 };
+
+    let content_length = response.content_length().unwrap_or(0);
+    let total = if resumed {
+        downloaded + content_length
+    } else {
+        content_length.max(asset.size.max(0) as u64)
+    };
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&partial_path)
+        .map_err(|e| UpdaterError::Network(e.to_string()))?;
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| UpdaterError::Network(e.to_string()))?;
+        file.write_all(&chunk).map_err(|e| UpdaterError::Network(e.to_string()))?;
+        downloaded += chunk.len() as u64;
+        on_progress(downloaded, total);
+    }
+    drop(file);
+
+    if total > 0 && downloaded < total {
+        // Connection dropped short; leave the partial file in place so the
+        // next attempt resumes from here instead of starting over.
+        return Err(UpdaterError::Network(format!(
+            "download interrupted at {} of {} bytes",
+            downloaded, total
+        )));
+    }
+
+    let data = std::fs::read(&partial_path).map_err(|e| UpdaterError::Network(e.to_string()))?;
+    let _ = std::fs::remove_file(&partial_path);
+    Ok(data)
+}
+
+/// Find the checksum asset matching this release and pull out the hash for
+/// `asset_name`. Returns `None` if no checksum is published, in which case
+/// verification is skipped rather than failing the update outright.
+async fn fetch_expected_checksum(
+    client: &reqwest::Client,
+    assets: &[GitHubAsset],
+    asset_name: &str,
+) -> Option<String> {
+    let own_checksum_name = format!("{}.sha256", asset_name).to_lowercase();
+    let checksum_asset = assets
+        .iter()
+        .find(|a| a.name.to_lowercase() == own_checksum_name)
+        .or_else(|| assets.iter().find(|a| a.name.to_lowercase().contains("checksum")))?;
+
+    let body = client
+        .get(&checksum_asset.browser_download_url)
+        .header("User-Agent", "Zest/1.0")
+        .send()
+        .await
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+
+    // Typical `sha256sum` output: "<hash>  <filename>" per line.
+    for line in body.lines() {
+        let mut parts = line.split_whitespace();
+        if let (Some(hash), Some(name)) = (parts.next(), parts.next()) {
+            if name.trim_start_matches('*') == asset_name {
+                return Some(hash.to_lowercase());
+            }
+        }
+    }
+
+    // Single-hash file named after the asset.
+    if checksum_asset.name.to_lowercase() != own_checksum_name {
+        return None;
+    }
+    body.split_whitespace().next().map(|h| h.to_lowercase())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Write `data` alongside the running executable and atomically rename it
+/// into place. On Windows, where a running executable can't be overwritten
+/// directly, the old one is moved aside first and cleaned up on next launch.
+fn swap_binary(data: &[u8]) -> Result<(), UpdaterError> {
+    let current_exe = std::env::current_exe().map_err(|e| UpdaterError::SwapFailed(e.to_string()))?;
+    let parent = current_exe
+        .parent()
+        .ok_or_else(|| UpdaterError::SwapFailed("executable has no parent directory".to_string()))?;
+    let file_name = current_exe
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or("zest");
+    let staged = parent.join(format!(".{}.new", file_name));
+
+    std::fs::write(&staged, data).map_err(|e| UpdaterError::SwapFailed(e.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| UpdaterError::SwapFailed(e.to_string()))?;
+    }
+
+    #[cfg(windows)]
+    {
+        let old = parent.join(format!("{}.old", file_name));
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(&current_exe, &old).map_err(|e| UpdaterError::SwapFailed(e.to_string()))?;
+    }
+
+    std::fs::rename(&staged, &current_exe).map_err(|e| UpdaterError::SwapFailed(e.to_string()))?;
+    Ok(())
+}
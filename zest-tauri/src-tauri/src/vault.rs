@@ -0,0 +1,172 @@
+//! Encrypted credential vault.
+//!
+//! `credentials::store_credential`/`get_credential` hand secrets straight
+//! to the OS-native store. This module adds an at-rest encryption layer in
+//! front of it: a 256-bit key is derived from a user-supplied master
+//! passphrase with Argon2id (random per-vault salt, stored in the vault
+//! header), and each credential value is encrypted with AES-256-GCM
+//! (random nonce per entry, authenticated) before it's handed to
+//! `credentials::store_credential`.
+//!
+//! The derived key lives in memory only while the vault is unlocked.
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::password_hash::SaltString;
+use argon2::Argon2;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use thiserror::Error;
+
+const NONCE_LEN: usize = 12;
+/// Known plaintext encrypted with the derived key so a wrong passphrase is
+/// detected without decrypting every stored credential.
+const VERIFICATION_PLAINTEXT: &[u8] = b"zest-vault-verify";
+
+#[derive(Error, Debug, Serialize)]
+pub enum VaultError {
+    #[error("Vault is locked")]
+    Locked,
+    #[error("No master passphrase has been set")]
+    NotInitialized,
+    #[error("Incorrect master passphrase")]
+    WrongPassphrase,
+    #[error("Vault crypto error: {0}")]
+    Crypto(String),
+    #[error("Vault storage error: {0}")]
+    Storage(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultHeader {
+    /// Argon2id salt string, stored so unlock can re-derive the same key.
+    salt: String,
+    /// base64(nonce || ciphertext) of `VERIFICATION_PLAINTEXT`.
+    verification_tag: String,
+}
+
+static VAULT_KEY: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+
+fn header_path() -> PathBuf {
+    crate::proxy::ProxyStateInner::data_dir().join("vault_header.json")
+}
+
+fn read_header() -> Result<Option<VaultHeader>, VaultError> {
+    let path = header_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| VaultError::Storage(e.to_string()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| VaultError::Storage(e.to_string()))
+}
+
+fn write_header(header: &VaultHeader) -> Result<(), VaultError> {
+    let path = header_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| VaultError::Storage(e.to_string()))?;
+    }
+    let content = serde_json::to_string_pretty(header).map_err(|e| VaultError::Storage(e.to_string()))?;
+    std::fs::write(&path, content).map_err(|e| VaultError::Storage(e.to_string()))
+}
+
+fn derive_key(passphrase: &str, salt: &SaltString) -> Result<[u8; 32], VaultError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt.as_str().as_bytes(), &mut key)
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+    Ok(key)
+}
+
+fn encrypt_with_key(key: &[u8; 32], plaintext: &[u8]) -> Result<String, VaultError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+
+    let mut combined = nonce_bytes.to_vec();
+    combined.extend_from_slice(&ciphertext);
+    Ok(base64::engine::general_purpose::STANDARD.encode(combined))
+}
+
+fn decrypt_with_key(key: &[u8; 32], encoded: &str) -> Result<Vec<u8>, VaultError> {
+    let combined = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+
+    if combined.len() < NONCE_LEN {
+        return Err(VaultError::Crypto("ciphertext too short".to_string()));
+    }
+    let (nonce_bytes, ciphertext) = combined.split_at(NONCE_LEN);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| VaultError::WrongPassphrase)
+}
+
+/// Set (or change) the master passphrase. Writes the salt and a
+/// verification tag, then leaves the vault unlocked with the new key.
+pub fn set_master_passphrase(passphrase: &str) -> Result<(), VaultError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let key = derive_key(passphrase, &salt)?;
+    let verification_tag = encrypt_with_key(&key, VERIFICATION_PLAINTEXT)?;
+
+    write_header(&VaultHeader {
+        salt: salt.to_string(),
+        verification_tag,
+    })?;
+
+    *VAULT_KEY.lock().map_err(|e| VaultError::Crypto(e.to_string()))? = Some(key);
+    Ok(())
+}
+
+/// Derive the key from `passphrase` and hold it in memory if it matches the
+/// stored verification tag.
+pub fn unlock_vault(passphrase: &str) -> Result<(), VaultError> {
+    let header = read_header()?.ok_or(VaultError::NotInitialized)?;
+    let salt = SaltString::from_b64(&header.salt).map_err(|e| VaultError::Crypto(e.to_string()))?;
+    let key = derive_key(passphrase, &salt)?;
+
+    let decrypted = decrypt_with_key(&key, &header.verification_tag)?;
+    if decrypted != VERIFICATION_PLAINTEXT {
+        return Err(VaultError::WrongPassphrase);
+    }
+
+    *VAULT_KEY.lock().map_err(|e| VaultError::Crypto(e.to_string()))? = Some(key);
+    Ok(())
+}
+
+/// Drop the derived key from memory.
+pub fn lock_vault() -> Result<(), VaultError> {
+    *VAULT_KEY.lock().map_err(|e| VaultError::Crypto(e.to_string()))? = None;
+    Ok(())
+}
+
+pub fn is_unlocked() -> bool {
+    VAULT_KEY.lock().map(|k| k.is_some()).unwrap_or(false)
+}
+
+/// Encrypt a credential value with the unlocked vault key.
+pub fn encrypt(plaintext: &str) -> Result<String, VaultError> {
+    let guard = VAULT_KEY.lock().map_err(|e| VaultError::Crypto(e.to_string()))?;
+    let key = guard.ok_or(VaultError::Locked)?;
+    encrypt_with_key(&key, plaintext.as_bytes())
+}
+
+/// Decrypt a credential value with the unlocked vault key.
+pub fn decrypt(encoded: &str) -> Result<String, VaultError> {
+    let guard = VAULT_KEY.lock().map_err(|e| VaultError::Crypto(e.to_string()))?;
+    let key = guard.ok_or(VaultError::Locked)?;
+    let plaintext = decrypt_with_key(&key, encoded)?;
+    String::from_utf8(plaintext).map_err(|e| VaultError::Crypto(e.to_string()))
+}